@@ -0,0 +1,55 @@
+#![no_main]
+
+use engine::{
+    collision::{collide_with_static, rects_collide, DefaultResolver},
+    rigid_body::RigidBody,
+    V2,
+};
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    pos: (i16, i16),
+    size: (u8, u8),
+    vel: (i32, i32),
+    others: Vec<((i16, i16), (u8, u8))>,
+}
+
+fn rect(pos: (i16, i16), size: (u8, u8)) -> (V2, V2) {
+    let size = V2::new(size.0 as f64 + 1.0, size.1 as f64 + 1.0);
+    (V2::new(pos.0 as f64, pos.1 as f64), size)
+}
+
+fuzz_target!(|input: Input| {
+    let delta = 1.0 / 60.0;
+    let (pos, size) = rect(input.pos, input.size);
+    let vel = V2::new(input.vel.0 as f64 / 100.0, input.vel.1 as f64 / 100.0);
+    let mut body = RigidBody::new().with_pos(pos).with_size(size).with_vel(vel);
+
+    let others = input
+        .others
+        .into_iter()
+        .take(8)
+        .map(|(pos, size)| {
+            let (pos, size) = rect(pos, size);
+            RigidBody::new().with_pos(pos).with_size(size)
+        })
+        .filter(|other| !rects_collide(pos, size, other.pos, other.size))
+        .collect::<Vec<_>>();
+
+    collide_with_static(&mut body, &others, &DefaultResolver, delta);
+    body.pos += body.vel.extend(delta);
+
+    assert!(body.pos.x.is_finite() && body.pos.y.is_finite());
+    // only the earliest collision on each axis is resolved, so the
+    // no-tunneling guarantee holds against a single body
+    if let [other] = others.as_slice() {
+        let margin = V2::new(1e-6, 1e-6);
+        assert!(!rects_collide(
+            body.pos + margin,
+            body.size - margin.extend(2.0),
+            other.pos,
+            other.size
+        ));
+    }
+});
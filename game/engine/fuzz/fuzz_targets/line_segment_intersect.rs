@@ -0,0 +1,39 @@
+#![no_main]
+
+use engine::{
+    physics::{Intersection, Line, Movable},
+    V2,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: [(i32, i32); 4]| {
+    // fixed point, screen sized coordinates with sub pixel steps
+    let [origin, delta_pos, p0, p1] =
+        input.map(|(x, y)| V2::new(x as f64 / 256.0, y as f64 / 256.0));
+
+    let moving = origin.moving(delta_pos);
+    let forward = moving.line_segment_intersect(Line::new(p0, p1));
+    let backward = moving.line_segment_intersect(Line::new(p1, p0));
+
+    for Intersection {
+        pos,
+        distance_factor,
+    } in forward.iter().chain(&backward)
+    {
+        assert!((0.0..=1.0).contains(distance_factor));
+        assert!(pos.x.is_finite() && pos.y.is_finite());
+    }
+    // where nearly parallel lines cross is too sensitive to rounding
+    let direction = p1 - p0;
+    let sin_angle = delta_pos.cross(direction).abs() / (delta_pos.len() * direction.len());
+    let scale = [origin, delta_pos, p0, p1]
+        .iter()
+        .fold(1.0, |scale: f64, v| scale.max(v.x.abs()).max(v.y.abs()));
+    if let (Some(forward), Some(backward)) = (forward, backward) {
+        let difference = (forward.pos - backward.pos).len();
+        assert!(
+            sin_angle < 1e-3 || difference <= scale * 1e-9,
+            "{difference}"
+        );
+    }
+});
@@ -1,15 +1,28 @@
-use std::{collections::HashSet, ops::ControlFlow, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+    rc::Rc,
+};
 
 use crate::{
     max, min,
-    physics::{Intersection, Line, Movable, Moving, OctoDirection, QuadDirection, Rect},
+    physics::{Intersection, Line, Movable, Moving, OctoDirection, Polygon, QuadDirection, Rect},
     query,
     rigid_body::RigidBody,
-    Component, Context, Error, Id, System, V2,
+    spawn, Component, Context, Error, Id, System, V2,
 };
 
 pub trait CollisionResolver {
     fn resolve(&self, body: &mut RigidBody, pos: V2, size: V2, dir: QuadDirection);
+
+    /// Resolves the velocity against an angled surface, the position is
+    /// corrected by the `CollisionSystem`. `normal` points away from the surface.
+    fn resolve_normal(&self, body: &mut RigidBody, normal: V2) {
+        let approach = body.vel.dot(normal);
+        if approach < 0.0 {
+            body.vel += normal.extend(-approach);
+        }
+    }
 }
 
 pub struct DefaultResolver;
@@ -53,15 +66,27 @@ pub fn resolve_position_default(body: &mut RigidBody, pos: V2, size: V2, dir: Qu
     }
 }
 
+pub const DEFAULT_LAYER: u32 = 1;
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+/// Both sides have to be in a layer the other masks.
+pub fn layers_interact(layer: u32, mask: u32, other_layer: u32, other_mask: u32) -> bool {
+    layer & other_mask != 0 && other_layer & mask != 0
+}
+
 #[derive(Component, Clone)]
 pub struct ShallowCollider {
     directions: HashSet<QuadDirection>,
+    pub layer: u32,
+    pub mask: u32,
 }
 
 impl ShallowCollider {
     pub fn new() -> Self {
         Self {
             directions: HashSet::new(),
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
         }
     }
 
@@ -69,14 +94,93 @@ impl ShallowCollider {
         self.directions.insert(dir);
         self
     }
+
+    pub fn collides_on(&self, dir: QuadDirection) -> bool {
+        self.directions.contains(&dir)
+    }
+
+    pub fn layer(self, layer: u32) -> Self {
+        Self { layer, ..self }
+    }
+
+    pub fn mask(self, mask: u32) -> Self {
+        Self { mask, ..self }
+    }
+}
+
+/// Static convex polygon, e.g. a slope, which resolving solid colliders
+/// slide along. Points are relative to the rigid body position.
+#[derive(Component, Clone)]
+pub struct PolygonCollider {
+    pub points: Vec<V2>,
+    pub layer: u32,
+    pub mask: u32,
+}
+
+impl PolygonCollider {
+    pub fn new(points: Vec<V2>) -> Self {
+        Self {
+            points,
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+        }
+    }
+
+    /// Right triangle filling `size`, rising towards the right if `rising`.
+    pub fn slope(size: V2, rising: bool) -> Self {
+        let points = if rising {
+            vec![V2::new(0.0, size.y), V2::new(size.x, 0.0), size]
+        } else {
+            vec![V2::new(0.0, 0.0), size, V2::new(0.0, size.y)]
+        };
+        Self::new(points)
+    }
+
+    pub fn layer(self, layer: u32) -> Self {
+        Self { layer, ..self }
+    }
+
+    pub fn mask(self, mask: u32) -> Self {
+        Self { mask, ..self }
+    }
+
+    pub fn polygon(&self, body: &RigidBody) -> Polygon {
+        Polygon::new(self.points.clone()).translated(body.pos)
+    }
+
+    /// Bounding box of the points, the size of the body isn't used.
+    pub fn bounds(&self, body: &RigidBody) -> (V2, V2) {
+        let mut lo = V2::new(f64::INFINITY, f64::INFINITY);
+        let mut hi = V2::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for point in &self.points {
+            lo = V2::new(min(lo.x, point.x), min(lo.y, point.y));
+            hi = V2::new(max(hi.x, point.x), max(hi.y, point.y));
+        }
+        (body.pos + lo, hi - lo)
+    }
 }
 
 #[derive(Component, Clone)]
 pub struct SolidCollider {
     pub resolver: Option<Rc<dyn CollisionResolver>>,
     pub colliding: Option<OctoDirection>,
+    /// Normal of the surface last collided with, pointing away from it.
+    pub normal: Option<V2>,
     pub size: Option<V2>,
     pub offset: V2,
+    /// Resolving colliders only push each other apart when both have this set.
+    pub body_collisions: bool,
+    /// Only used between two resolving colliders, infinite mass never moves.
+    /// A mass of zero or less counts as infinite.
+    pub mass: f64,
+    /// Share of the approaching velocity kept when two resolving colliders hit.
+    pub restitution: f64,
+    pub layer: u32,
+    pub mask: u32,
+    /// Whether the body is standing on a shallow collider.
+    pub on_shallow: bool,
+    /// Shallow colliders are ignored while this is above zero.
+    pub drop_through_timer: f64,
 }
 
 impl SolidCollider {
@@ -84,8 +188,34 @@ impl SolidCollider {
         Self {
             resolver: None,
             colliding: None,
+            normal: None,
             size: None,
             offset: V2::new(0.0, 0.0),
+            body_collisions: false,
+            mass: 1.0,
+            restitution: 0.0,
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+            on_shallow: false,
+            drop_through_timer: 0.0,
+        }
+    }
+
+    /// Falls through shallow colliders, e.g. one-way platforms, for the duration.
+    pub fn drop_through(&mut self, duration: f64) {
+        self.drop_through_timer = max(self.drop_through_timer, duration);
+        self.on_shallow = false;
+    }
+
+    pub fn interacts_with(&self, other: &SolidCollider) -> bool {
+        layers_interact(self.layer, self.mask, other.layer, other.mask)
+    }
+
+    fn inverse_mass(&self) -> f64 {
+        if self.mass > 0.0 {
+            self.mass.recip()
+        } else {
+            0.0
         }
     }
 
@@ -105,12 +235,332 @@ impl SolidCollider {
     pub fn offset(self, offset: V2) -> Self {
         Self { offset, ..self }
     }
+
+    /// Collides with other resolving colliders which have body collisions.
+    pub fn with_body_collisions(self) -> Self {
+        Self {
+            body_collisions: true,
+            ..self
+        }
+    }
+
+    pub fn mass(self, mass: f64) -> Self {
+        Self { mass, ..self }
+    }
+
+    pub fn restitution(self, restitution: f64) -> Self {
+        Self {
+            restitution,
+            ..self
+        }
+    }
+
+    pub fn layer(self, layer: u32) -> Self {
+        Self { layer, ..self }
+    }
+
+    pub fn mask(self, mask: u32) -> Self {
+        Self { mask, ..self }
+    }
 }
 
-struct Collision {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TriggerEvent {
+    Enter(Id),
+    Stay(Id),
+    Exit(Id),
+}
+
+/// Non-resolving collider, reports which triggers and solid colliders it
+/// overlaps. Events are replaced every update of the `TriggerSystem`.
+#[derive(Component, Clone)]
+pub struct Trigger {
+    pub size: Option<V2>,
+    pub offset: V2,
+    pub layer: u32,
+    pub mask: u32,
+    overlapping: Vec<Id>,
+    events: Vec<TriggerEvent>,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Trigger {
+    pub fn new() -> Self {
+        Self {
+            size: None,
+            offset: V2::new(0.0, 0.0),
+            layer: DEFAULT_LAYER,
+            mask: ALL_LAYERS,
+            overlapping: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn size(self, size: V2) -> Self {
+        Self {
+            size: Some(size),
+            ..self
+        }
+    }
+
+    pub fn offset(self, offset: V2) -> Self {
+        Self { offset, ..self }
+    }
+
+    pub fn layer(self, layer: u32) -> Self {
+        Self { layer, ..self }
+    }
+
+    pub fn mask(self, mask: u32) -> Self {
+        Self { mask, ..self }
+    }
+
+    pub fn bounds(&self, body: &RigidBody) -> (V2, V2) {
+        (body.pos + self.offset, self.size.unwrap_or(body.size))
+    }
+
+    pub fn overlapping(&self) -> &[Id] {
+        &self.overlapping
+    }
+
+    pub fn events(&self) -> &[TriggerEvent] {
+        &self.events
+    }
+
+    pub fn entered(&self) -> impl Iterator<Item = Id> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            TriggerEvent::Enter(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    pub fn exited(&self) -> impl Iterator<Item = Id> + '_ {
+        self.events.iter().filter_map(|event| match event {
+            TriggerEvent::Exit(id) => Some(*id),
+            _ => None,
+        })
+    }
+}
+
+struct TriggerTarget {
+    id: Id,
     pos: V2,
-    direction: QuadDirection,
-    distance_factor: f64,
+    size: V2,
+    layer: u32,
+    mask: u32,
+}
+
+pub struct TriggerSystem(pub u64);
+impl System for TriggerSystem {
+    fn on_update(&self, ctx: &mut Context, _delta: f64) -> Result<(), Error> {
+        let mut targets = Vec::new();
+        for id in query!(ctx, RigidBody, Trigger) {
+            let body = ctx.select::<RigidBody>(id).clone();
+            let trigger = ctx.select::<Trigger>(id);
+            let (pos, size) = trigger.bounds(&body);
+            let (layer, mask) = (trigger.layer, trigger.mask);
+            targets.push(TriggerTarget {
+                id,
+                pos,
+                size,
+                layer,
+                mask,
+            });
+        }
+        for id in query!(ctx, RigidBody, SolidCollider) {
+            let body = ctx.select::<RigidBody>(id).clone();
+            let collider = ctx.select::<SolidCollider>(id);
+            let (layer, mask) = (collider.layer, collider.mask);
+            targets.push(TriggerTarget {
+                id,
+                pos: body.pos,
+                size: body.size,
+                layer,
+                mask,
+            });
+        }
+
+        // the grid holds indices into `targets`, an entity with both a
+        // trigger and a solid collider is a target once for each
+        let mut grid = SpatialGrid::new(256.0);
+        for (index, target) in targets.iter().enumerate() {
+            grid.insert(index as Id, target.pos, target.size);
+        }
+
+        for id in query!(ctx, RigidBody, Trigger) {
+            let body = ctx.select::<RigidBody>(id).clone();
+            let trigger = ctx.select::<Trigger>(id);
+            let (pos, size) = trigger.bounds(&body);
+
+            let mut overlapping = grid
+                .query(pos, size)
+                .into_iter()
+                .map(|index| &targets[index as usize])
+                .filter(|target| {
+                    target.id != id
+                        && layers_interact(trigger.layer, trigger.mask, target.layer, target.mask)
+                        && rects_collide(pos, size, target.pos, target.size)
+                })
+                .map(|target| target.id)
+                .collect::<Vec<_>>();
+            overlapping.sort_unstable();
+            overlapping.dedup();
+
+            let mut events = overlapping
+                .iter()
+                .map(|other_id| match trigger.overlapping.contains(other_id) {
+                    true => TriggerEvent::Stay(*other_id),
+                    false => TriggerEvent::Enter(*other_id),
+                })
+                .collect::<Vec<_>>();
+            events.extend(
+                trigger
+                    .overlapping
+                    .iter()
+                    .filter(|other_id| !overlapping.contains(other_id))
+                    .map(|other_id| TriggerEvent::Exit(*other_id)),
+            );
+            trigger.overlapping = overlapping;
+            trigger.events = events;
+        }
+        Ok(())
+    }
+}
+
+/// Colliders by where they are this step, kept by the `CollisionSystem`
+/// for the queries in `raycast`. Bodies are in the cells of their motion,
+/// so the grids stay valid until the next update.
+#[derive(Component, Clone)]
+pub struct ColliderGrids {
+    pub solids: SpatialGrid,
+    pub shallows: SpatialGrid,
+    pub polygons: SpatialGrid,
+}
+
+impl ColliderGrids {
+    const CELL_SIZE: f64 = 256.0;
+
+    pub fn build(ctx: &mut Context, delta: f64) -> Self {
+        let shallows = SpatialGrid::from_bodies(
+            ctx,
+            query!(ctx, RigidBody, ShallowCollider),
+            Self::CELL_SIZE,
+            delta,
+        );
+        let mut polygons = SpatialGrid::new(Self::CELL_SIZE);
+        for id in query!(ctx, RigidBody, PolygonCollider) {
+            let body = ctx.select::<RigidBody>(id).clone();
+            let (pos, size) = ctx.select::<PolygonCollider>(id).bounds(&body);
+            polygons.insert(id, pos, size);
+        }
+        Self {
+            solids: Self::solid_grid(ctx, delta),
+            shallows,
+            polygons,
+        }
+    }
+
+    fn solid_grid(ctx: &mut Context, delta: f64) -> SpatialGrid {
+        let ids = query!(ctx, RigidBody, SolidCollider);
+        SpatialGrid::from_bodies(ctx, ids, Self::CELL_SIZE, delta)
+    }
+}
+
+pub fn rects_collide(pos_a: V2, size_a: V2, pos_b: V2, size_b: V2) -> bool {
+    pos_a.x < pos_b.x + size_b.x
+        && pos_a.x + size_a.x > pos_b.x
+        && pos_a.y < pos_b.y + size_b.y
+        && pos_a.y + size_a.y > pos_b.y
+}
+
+/// Uniform grid broad phase. Queries return every id sharing a cell with
+/// the area, so callers still need to do the exact check.
+#[derive(Clone, Default)]
+pub struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<Id>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Grid of the rigid bodies of the entities, over their motion this step.
+    pub fn from_bodies<I: IntoIterator<Item = Id>>(
+        ctx: &mut Context,
+        ids: I,
+        cell_size: f64,
+        delta: f64,
+    ) -> Self {
+        let mut grid = Self::new(cell_size);
+        for id in ids {
+            let (pos, size) = swept_bounds(ctx.select::<RigidBody>(id), delta);
+            grid.insert(id, pos, size);
+        }
+        grid
+    }
+
+    /// Bounds touching a cell border are in the cells on both sides.
+    fn cells_of(&self, pos: V2, size: V2) -> impl Iterator<Item = (i64, i64)> {
+        let cell = |v: f64| (v / self.cell_size).floor() as i64;
+        let (x0, y0) = (cell(pos.x), cell(pos.y));
+        let (x1, y1) = (cell(pos.x + size.x), cell(pos.y + size.y));
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+    }
+
+    pub fn insert(&mut self, id: Id, pos: V2, size: V2) {
+        for cell in self.cells_of(pos, size).collect::<Vec<_>>() {
+            self.cells.entry(cell).or_default().push(id);
+        }
+    }
+
+    pub fn query(&self, pos: V2, size: V2) -> Vec<Id> {
+        let mut ids = self
+            .cells_of(pos, size)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Bounds of the body over the whole step, with a margin so bodies
+/// resting against each other are still found.
+pub fn swept_bounds(body: &RigidBody, delta: f64) -> (V2, V2) {
+    let margin = 1.0;
+    let delta_pos = body.vel.extend(delta);
+    let pos = V2::new(
+        min(body.pos.x, body.pos.x + delta_pos.x) - margin,
+        min(body.pos.y, body.pos.y + delta_pos.y) - margin,
+    );
+    let size = body.size
+        + V2::new(delta_pos.x.abs(), delta_pos.y.abs())
+        + V2::new(margin, margin).extend(2.0);
+    (pos, size)
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Collision {
+    pub pos: V2,
+    pub direction: QuadDirection,
+    /// Normal of the side collided with, pointing away from the other body.
+    pub normal: V2,
+    pub distance_factor: f64,
+    /// Velocity of the body collided with, e.g. a moving platform.
+    pub other_vel: V2,
+    pub shallow: bool,
 }
 
 pub struct CollisionSystem(pub u64);
@@ -118,6 +568,8 @@ impl System for CollisionSystem {
     fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
         use QuadDirection::*;
 
+        let mut grids = ColliderGrids::build(ctx, delta);
+
         for id in query!(ctx, RigidBody, SolidCollider) {
             let collider = ctx.select::<SolidCollider>(id).clone();
             let Some(resolver) = collider.resolver else {
@@ -126,41 +578,235 @@ impl System for CollisionSystem {
 
             let collider = ctx.select::<SolidCollider>(id);
             collider.colliding = None;
+            collider.normal = None;
+            collider.on_shallow = false;
+            collider.drop_through_timer = max(0.0, collider.drop_through_timer - delta);
+            let dropping_through = collider.drop_through_timer > 0.0;
 
             let body = ctx.select::<RigidBody>(id).clone();
+            let (pos, size) = swept_bounds(&body, delta);
 
             let mut collisions = Vec::<Collision>::new();
-            find_shallow_collisions(&mut collisions, ctx, id, &body, delta);
-            find_solid_collisions(&mut collisions, ctx, id, &body, delta);
+            if !dropping_through {
+                let candidates = grids.shallows.query(pos, size);
+                find_shallow_collisions(&mut collisions, ctx, id, &body, delta, candidates);
+            }
+            let candidates = grids.solids.query(pos, size);
+            find_solid_collisions(&mut collisions, ctx, id, &body, delta, candidates);
+
+            let body = ctx.select::<RigidBody>(id);
+            let resolved = resolve_collisions(body, collisions, resolver.as_ref(), delta);
+            for int in resolved {
+                let collider = ctx.select::<SolidCollider>(id);
+                collider.colliding = Some(int.direction.into());
+                collider.normal = Some(int.normal);
+                if int.direction == Bottom {
+                    collider.on_shallow = int.shallow;
+                }
+            }
+        }
+        resolve_polygon_collisions(ctx, &grids.polygons, delta);
+        resolve_body_collisions(ctx, &grids.solids, delta);
+
+        // resolving moved the bodies, queries need them where they are now
+        grids.solids = ColliderGrids::solid_grid(ctx, delta);
+        match query!(ctx, ColliderGrids).first() {
+            Some(id) => *ctx.select::<ColliderGrids>(*id) = grids,
+            None => {
+                spawn!(ctx, grids);
+            }
+        }
+        Ok(())
+    }
 
-            collisions.sort_by(|a, b| a.distance_factor.total_cmp(&b.distance_factor));
+    fn on_remove(&self, ctx: &mut Context) -> Result<(), Error> {
+        for id in query!(ctx, ColliderGrids) {
+            ctx.despawn(id);
+        }
+        Ok(())
+    }
+}
 
-            let horizontal_collisions = collisions
-                .iter()
-                .filter(|c| match c.direction {
-                    Left | Right => true,
-                    Top | Bottom => false,
-                })
-                .collect::<Vec<_>>();
+/// Resolves the body against bodies standing still, the way the
+/// `CollisionSystem` does, without needing a `Context`.
+pub fn collide_with_static(
+    body: &mut RigidBody,
+    others: &[RigidBody],
+    resolver: &dyn CollisionResolver,
+    delta: f64,
+) -> Vec<QuadDirection> {
+    let mut collisions = Vec::new();
+    for other in others {
+        find_collisions(&mut collisions, body, other, delta, |_, _| true);
+    }
+    resolve_collisions(body, collisions, resolver, delta)
+        .into_iter()
+        .map(|collision| collision.direction)
+        .collect()
+}
 
-            let vertical_collisions = collisions
-                .iter()
-                .filter(|c| match c.direction {
-                    Left | Right => false,
-                    Top | Bottom => true,
-                })
-                .collect::<Vec<_>>();
+/// Resolves the earliest horizontal and the earliest vertical collision,
+/// returning the ones resolved.
+pub(crate) fn resolve_collisions(
+    body: &mut RigidBody,
+    mut collisions: Vec<Collision>,
+    resolver: &dyn CollisionResolver,
+    delta: f64,
+) -> Vec<Collision> {
+    use QuadDirection::*;
 
-            for collision in [horizontal_collisions.first(), vertical_collisions.first()] {
-                if let Some(int) = collision {
-                    let collider = ctx.select::<SolidCollider>(id);
-                    collider.colliding = Some(int.direction.into());
-                    let body = ctx.select::<RigidBody>(id);
-                    resolver.resolve(body, int.pos, body.size, int.direction)
-                }
+    collisions.sort_by(|a, b| a.distance_factor.total_cmp(&b.distance_factor));
+    let horizontal = collisions
+        .iter()
+        .find(|c| matches!(c.direction, Left | Right))
+        .cloned();
+    let vertical = collisions
+        .iter()
+        .find(|c| matches!(c.direction, Top | Bottom))
+        .cloned();
+
+    let resolved = [horizontal, vertical]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    for int in &resolved {
+        // resolve relative to the other body, so it pushes and
+        // carries along what it collides with
+        body.vel = body.vel - int.other_vel;
+        resolver.resolve(body, int.pos, body.size, int.direction);
+        body.vel += int.other_vel;
+        if int.direction == Bottom {
+            body.pos.x += int.other_vel.x * delta;
+        }
+    }
+    resolved
+}
+
+/// Pushes resolving bodies out of polygon colliders along the surface
+/// normal, keeping the velocity along the surface so they slide.
+fn resolve_polygon_collisions(ctx: &mut Context, polygons: &SpatialGrid, delta: f64) {
+    for id in query!(ctx, RigidBody, SolidCollider) {
+        let collider = ctx.select::<SolidCollider>(id).clone();
+        let Some(resolver) = collider.resolver else {
+            continue;
+        };
+        let body = ctx.select::<RigidBody>(id).clone();
+        let (pos, size) = swept_bounds(&body, delta);
+        for other_id in polygons.query(pos, size) {
+            let other_body = ctx.select::<RigidBody>(other_id).clone();
+            let other_collider = ctx.select::<PolygonCollider>(other_id);
+            if !layers_interact(
+                collider.layer,
+                collider.mask,
+                other_collider.layer,
+                other_collider.mask,
+            ) {
+                continue;
+            }
+            let other_polygon = other_collider.polygon(&other_body);
+
+            let body = ctx.select::<RigidBody>(id);
+            let next_pos = body.pos + body.vel.extend(delta);
+            let polygon = Polygon::from_rect(Rect::new(next_pos, body.size));
+            let Some((normal, depth)) = polygon.penetration(&other_polygon) else {
+                continue;
+            };
+
+            // the velocity change already stops part of the overlap
+            let approach = -body.vel.dot(normal);
+            resolver.resolve_normal(body, normal);
+            let stopped = (approach + body.vel.dot(normal)) * delta;
+            body.pos += normal.extend(max(0.0, depth - stopped));
+
+            let collider = ctx.select::<SolidCollider>(id);
+            collider.colliding = OctoDirection::nearest(normal.reverse());
+            collider.normal = Some(normal);
+        }
+    }
+}
+
+/// Pushes overlapping resolving bodies apart by their mass and exchanges
+/// the approaching velocity, scaled by restitution.
+fn resolve_body_collisions(ctx: &mut Context, solids: &SpatialGrid, delta: f64) {
+    for id in query!(ctx, RigidBody, SolidCollider) {
+        let collider = ctx.select::<SolidCollider>(id).clone();
+        if collider.resolver.is_none() || !collider.body_collisions {
+            continue;
+        }
+        let body = ctx.select::<RigidBody>(id).clone();
+        let (pos, size) = swept_bounds(&body, delta);
+        for other_id in solids.query(pos, size) {
+            if other_id <= id {
+                continue;
+            }
+            let other_collider = ctx.select::<SolidCollider>(other_id).clone();
+            if other_collider.resolver.is_none()
+                || !other_collider.body_collisions
+                || !collider.interacts_with(&other_collider)
+            {
+                continue;
+            }
+            let body = ctx.select::<RigidBody>(id).clone();
+            let other_body = ctx.select::<RigidBody>(other_id).clone();
+            let Some((normal, depth)) = penetration(&body, &other_body, delta) else {
+                continue;
+            };
+
+            let inverse_mass = collider.inverse_mass();
+            let other_inverse_mass = other_collider.inverse_mass();
+            let total_inverse_mass = inverse_mass + other_inverse_mass;
+            if total_inverse_mass <= 0.0 {
+                continue;
+            }
+
+            let approach = (other_body.vel.x - body.vel.x) * normal.x
+                + (other_body.vel.y - body.vel.y) * normal.y;
+            let restitution = (collider.restitution + other_collider.restitution) / 2.0;
+            let impulse = if approach < 0.0 {
+                -(1.0 + restitution) * approach / total_inverse_mass
+            } else {
+                0.0
+            };
+
+            let body = ctx.select::<RigidBody>(id);
+            body.pos += normal.extend(-depth * inverse_mass / total_inverse_mass);
+            body.vel += normal.extend(-impulse * inverse_mass);
+            let other_body = ctx.select::<RigidBody>(other_id);
+            other_body.pos += normal.extend(depth * other_inverse_mass / total_inverse_mass);
+            other_body.vel += normal.extend(impulse * other_inverse_mass);
+
+            if let Some(direction) = OctoDirection::from_v2(normal) {
+                let collider = ctx.select::<SolidCollider>(id);
+                collider.colliding = collider.colliding.or(Some(direction));
+                collider.normal = collider.normal.or(Some(normal.reverse()));
+                let other_collider = ctx.select::<SolidCollider>(other_id);
+                other_collider.colliding = other_collider.colliding.or(Some(direction.reverse()));
+                other_collider.normal = other_collider.normal.or(Some(normal));
             }
         }
-        Ok(())
+    }
+}
+
+/// Axis of least overlap after this step, pointing from the body to the
+/// other body, and how deep they overlap along it.
+fn penetration(body: &RigidBody, other_body: &RigidBody, delta: f64) -> Option<(V2, f64)> {
+    let pos = body.pos + body.vel.extend(delta);
+    let other_pos = other_body.pos + other_body.vel.extend(delta);
+    if !rects_collide(pos, body.size, other_pos, other_body.size) {
+        return None;
+    }
+    let center = pos + body.size.div_comps(2.0);
+    let other_center = other_pos + other_body.size.div_comps(2.0);
+    let overlap_x =
+        min(pos.x + body.size.x, other_pos.x + other_body.size.x) - max(pos.x, other_pos.x);
+    let overlap_y =
+        min(pos.y + body.size.y, other_pos.y + other_body.size.y) - max(pos.y, other_pos.y);
+    if overlap_x < overlap_y {
+        let sign = if other_center.x < center.x { -1.0 } else { 1.0 };
+        Some((V2::new(sign, 0.0), overlap_x))
+    } else {
+        let sign = if other_center.y < center.y { -1.0 } else { 1.0 };
+        Some((V2::new(0.0, sign), overlap_y))
     }
 }
 
@@ -170,15 +816,19 @@ fn find_solid_collisions(
     id: u64,
     body: &RigidBody,
     delta: f64,
+    candidates: Vec<Id>,
 ) {
-    for other_id in query!(ctx, RigidBody, SolidCollider) {
+    let collider = ctx.select::<SolidCollider>(id).clone();
+    for other_id in candidates {
         if id == other_id {
             continue;
         }
 
-        let collider = ctx.select::<SolidCollider>(id).clone();
-        let other_collider = ctx.select::<SolidCollider>(other_id).clone();
-        if other_collider.resolver.is_some() && collider.resolver.is_some() {
+        // pairs of resolving bodies are pushed apart by `resolve_body_collisions`
+        let other_collider = ctx.select::<SolidCollider>(other_id);
+        if (collider.resolver.is_some() && other_collider.resolver.is_some())
+            || !collider.interacts_with(other_collider)
+        {
             continue;
         }
 
@@ -202,22 +852,36 @@ fn find_shallow_collisions(
     id: Id,
     body: &RigidBody,
     delta: f64,
+    candidates: Vec<Id>,
 ) {
-    for other_id in query!(ctx, RigidBody, ShallowCollider) {
+    let collider = ctx.select::<SolidCollider>(id).clone();
+    for other_id in candidates {
         if id == other_id {
             continue;
         }
 
         let other_body = ctx.select::<RigidBody>(other_id).clone();
-        let other_collider = ctx.select::<ShallowCollider>(other_id).clone();
+        let other_collider = ctx.select::<ShallowCollider>(other_id);
+        if !layers_interact(
+            collider.layer,
+            collider.mask,
+            other_collider.layer,
+            other_collider.mask,
+        ) {
+            continue;
+        }
 
+        let first_found = collisions.len();
         find_collisions(collisions, body, &other_body, delta, |side, delta_pos| {
             other_collider.directions.contains(&side) && correct_delta_pos(side.into(), delta_pos)
         });
+        for collision in &mut collisions[first_found..] {
+            collision.shallow = true;
+        }
     }
 }
 
-fn find_collisions<F: Fn(QuadDirection, V2) -> bool>(
+pub(crate) fn find_collisions<F: Fn(QuadDirection, V2) -> bool>(
     intersections: &mut Vec<Collision>,
     body: &RigidBody,
     other_body: &RigidBody,
@@ -226,7 +890,8 @@ fn find_collisions<F: Fn(QuadDirection, V2) -> bool>(
 ) {
     use QuadDirection::*;
 
-    let delta_pos = body.vel.extend(delta);
+    // motion relative to the other body, which is treated as standing still
+    let delta_pos = (body.vel - other_body.vel).extend(delta);
     let rect = Rect::new(body.pos, body.size).moving(delta_pos);
 
     let other_rect = Rect::new(other_body.pos, other_body.size);
@@ -252,7 +917,10 @@ fn find_collisions<F: Fn(QuadDirection, V2) -> bool>(
                 intersections.push(Collision {
                     pos,
                     direction: side.reverse(),
+                    normal: side.normal(),
                     distance_factor,
+                    other_vel: other_body.vel,
+                    shallow: false,
                 });
                 return;
             }
@@ -268,10 +936,69 @@ fn find_collisions<F: Fn(QuadDirection, V2) -> bool>(
                 intersections.push(Collision {
                     pos: p,
                     direction: side.reverse(),
+                    normal: side.normal(),
                     distance_factor,
+                    other_vel: other_body.vel,
+                    shallow: false,
                 });
                 return;
             }
         }
     }
 }
+
+#[test]
+fn spatial_grid_query() {
+    let mut grid = SpatialGrid::new(100.0);
+    grid.insert(0, V2::new(0.0, 0.0), V2::new(50.0, 50.0));
+    grid.insert(1, V2::new(150.0, 0.0), V2::new(400.0, 50.0));
+    grid.insert(2, V2::new(-300.0, 500.0), V2::new(10.0, 10.0));
+
+    assert_eq!(
+        grid.query(V2::new(40.0, 40.0), V2::new(20.0, 20.0)),
+        vec![0]
+    );
+    assert_eq!(
+        grid.query(V2::new(90.0, 0.0), V2::new(500.0, 10.0)),
+        vec![0, 1]
+    );
+    assert_eq!(
+        grid.query(V2::new(-295.0, 505.0), V2::new(1.0, 1.0)),
+        vec![2]
+    );
+    assert!(grid
+        .query(V2::new(1000.0, 1000.0), V2::new(1.0, 1.0))
+        .is_empty());
+}
+
+#[test]
+fn polygon_bounds_ignore_body_size() {
+    // bodies of polygons are spawned without a size
+    let body = RigidBody::new().with_pos(V2::new(1000.0, 500.0));
+    let slope = PolygonCollider::slope(V2::new(300.0, 100.0), true);
+    let (pos, size) = slope.bounds(&body);
+    assert_eq!((pos.x, pos.y), (1000.0, 500.0));
+    assert_eq!((size.x, size.y), (300.0, 100.0));
+
+    let mut grid = SpatialGrid::new(256.0);
+    grid.insert(0, pos, size);
+    let player = RigidBody::new()
+        .with_pos(V2::new(1250.0, 480.0))
+        .with_size(V2::new(20.0, 40.0));
+    let (player_pos, player_size) = swept_bounds(&player, 1.0 / 60.0);
+    assert_eq!(grid.query(player_pos, player_size), vec![0]);
+
+    let player_polygon = Polygon::from_rect(Rect::new(player.pos, player.size));
+    let (normal, _) = player_polygon
+        .penetration(&slope.polygon(&body))
+        .expect("player overlaps the slope");
+    assert!(normal.y < 0.0);
+}
+
+#[test]
+fn solid_collider_inverse_mass() {
+    assert_eq!(SolidCollider::new().mass(2.0).inverse_mass(), 0.5);
+    assert_eq!(SolidCollider::new().mass(0.0).inverse_mass(), 0.0);
+    assert_eq!(SolidCollider::new().mass(f64::INFINITY).inverse_mass(), 0.0);
+    assert!(!SolidCollider::new().body_collisions);
+}
@@ -8,7 +8,9 @@ use std::{
 };
 
 use sdl2::{
-    controller::{Button as ControllerButton, GameController as SdlGameController},
+    controller::{
+        Axis as ControllerAxis, Button as ControllerButton, GameController as SdlGameController,
+    },
     image::LoadTexture,
     keyboard::Keycode,
     mouse::MouseButton,
@@ -332,14 +334,14 @@ impl<'context, 'game> Context<'context, 'game> {
         } else {
             texture.set_alpha_mod(255);
         }
-        if let Some(angle) = opts.angle {
+        if opts.angle.is_some() || opts.flip_horizontal {
             self.game.canvas.copy_ex(
                 texture,
                 None,
                 Rect::new(pos.x as i32, pos.y as i32, size.x as u32, size.y as u32),
-                angle,
+                opts.angle.unwrap_or(0.0),
                 Point::new(0, 0),
-                false,
+                opts.flip_horizontal,
                 false,
             )?;
         } else {
@@ -448,6 +450,68 @@ impl<'context, 'game> Context<'context, 'game> {
             .unwrap_or(&false)
     }
 
+    pub fn keys_just_pressed(&self) -> Vec<Keycode> {
+        self.game
+            .currently_pressed_keys
+            .iter()
+            .filter_map(|(key, just_pressed)| just_pressed.then_some(*key))
+            .collect()
+    }
+
+    pub fn mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.game
+            .currently_pressed_mouse_buttons
+            .contains_key(&button)
+    }
+
+    pub fn mouse_buttons_just_pressed(&self) -> Vec<MouseButton> {
+        self.game
+            .currently_pressed_mouse_buttons
+            .iter()
+            .filter_map(|(button, just_pressed)| just_pressed.then_some(*button))
+            .collect()
+    }
+
+    /// Also true for repeats while the key is held, e.g. for text editing.
+    pub fn key_pressed_or_repeated(&self, keycode: Keycode) -> bool {
+        self.game.repeated_keys.contains(&keycode)
+    }
+
+    /// Text typed this frame, already composed by the IME if one is used.
+    pub fn text_input(&self) -> &str {
+        &self.game.text_input
+    }
+
+    /// Text currently being composed by the IME, not yet part of `text_input`.
+    pub fn text_editing(&self) -> Option<&str> {
+        self.game.text_editing.as_deref()
+    }
+
+    pub fn start_text_input(&mut self) {
+        self.game.video_subsystem.text_input().start();
+    }
+
+    pub fn stop_text_input(&mut self) {
+        self.game.video_subsystem.text_input().stop();
+        self.game.text_editing = None;
+    }
+
+    pub fn clipboard_text(&self) -> Option<String> {
+        let clipboard = self.game.video_subsystem.clipboard();
+        if !clipboard.has_clipboard_text() {
+            return None;
+        }
+        clipboard.clipboard_text().ok()
+    }
+
+    pub fn set_clipboard_text(&mut self, text: &str) -> Result<(), Error> {
+        self.game
+            .video_subsystem
+            .clipboard()
+            .set_clipboard_text(text)?;
+        Ok(())
+    }
+
     pub fn mouse_position(&self) -> (i32, i32) {
         self.game.mouse_position
     }
@@ -462,6 +526,37 @@ impl<'context, 'game> Context<'context, 'game> {
             .unwrap()
     }
 
+    pub fn controller_position(&self, id: Id) -> Option<&ControllerPosition> {
+        self.game
+            .controllers
+            .iter()
+            .find(|v| v.0 == id)
+            .map(|v| &v.2)
+    }
+
+    /// Strengths are between 0 and 1, duration is in seconds.
+    pub fn controller_rumble(
+        &mut self,
+        id: Id,
+        low_frequency: f64,
+        high_frequency: f64,
+        duration: f64,
+    ) -> Result<(), Error> {
+        let (_, controller, _) = self
+            .game
+            .controllers
+            .iter_mut()
+            .find(|v| v.0 == id)
+            .ok_or("tried to rumble unregistered controller")?;
+        let strength = |value: f64| (value.clamp(0.0, 1.0) * u16::MAX as f64) as u16;
+        controller.set_rumble(
+            strength(low_frequency),
+            strength(high_frequency),
+            (duration * 1000.0) as u32,
+        )?;
+        Ok(())
+    }
+
     pub fn controller_button_pressed(&self, id: Id, button: ControllerButton) -> bool {
         self.game
             .currently_pressed_controller_buttons
@@ -476,6 +571,29 @@ impl<'context, 'game> Context<'context, 'game> {
             .unwrap_or(&false)
     }
 
+    pub fn controller_buttons_just_pressed(&self) -> Vec<(Id, ControllerButton)> {
+        self.game
+            .currently_pressed_controller_buttons
+            .iter()
+            .filter_map(|(button, just_pressed)| just_pressed.then_some(*button))
+            .collect()
+    }
+
+    /// Sticks are between -1 and 1, triggers between 0 and 1.
+    pub fn controller_axis(&self, id: Id, axis: ControllerAxis) -> f64 {
+        let Some(position) = self.controller_position(id) else {
+            return 0.0;
+        };
+        match axis {
+            ControllerAxis::LeftX => position.left_stick.x,
+            ControllerAxis::LeftY => position.left_stick.y,
+            ControllerAxis::RightX => position.right_stick.x,
+            ControllerAxis::RightY => position.right_stick.y,
+            ControllerAxis::TriggerLeft => position.left_trigger,
+            ControllerAxis::TriggerRight => position.right_trigger,
+        }
+    }
+
     pub fn active_controllers(&self) -> impl Iterator<Item = Id> {
         self.game
             .controllers
@@ -491,6 +609,7 @@ pub struct DrawTextureOpts {
     pub opacity: Option<f64>,
     pub size: Option<V2>,
     pub angle: Option<f64>,
+    pub flip_horizontal: bool,
 }
 
 impl DrawTextureOpts {
@@ -500,6 +619,7 @@ impl DrawTextureOpts {
             opacity: None,
             size: None,
             angle: None,
+            flip_horizontal: false,
         }
     }
     pub fn size(self, size: V2) -> Self {
@@ -523,4 +643,10 @@ impl DrawTextureOpts {
     pub fn angle(self, angle: Option<f64>) -> Self {
         Self { angle, ..self }
     }
+    pub fn flip_horizontal(self, flip_horizontal: bool) -> Self {
+        Self {
+            flip_horizontal,
+            ..self
+        }
+    }
 }
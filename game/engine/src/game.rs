@@ -28,7 +28,6 @@ use super::{Component, Error};
 pub struct Game<'game> {
     #[allow(dead_code)]
     pub(crate) sdl_context: Sdl,
-    #[allow(dead_code)]
     pub(crate) video_subsystem: VideoSubsystem,
     #[allow(dead_code)]
     pub(crate) controller_subsystem: GameControllerSubsystem,
@@ -48,6 +47,9 @@ pub struct Game<'game> {
     pub(crate) text_textures: HashMap<TextTextureKey, Text>,
     pub(crate) fonts: Vec<(Id, u16, PathBuf, Font<'game>)>,
     pub(crate) currently_pressed_keys: HashMap<Keycode, bool>,
+    pub(crate) repeated_keys: Vec<Keycode>,
+    pub(crate) text_input: String,
+    pub(crate) text_editing: Option<String>,
     pub(crate) currently_pressed_mouse_buttons: HashMap<MouseButton, bool>,
     pub(crate) currently_pressed_controller_buttons: HashMap<(Id, ControllerButton), bool>,
     pub(crate) controllers: Vec<(Id, SdlGameController, ControllerPosition)>,
@@ -55,7 +57,7 @@ pub struct Game<'game> {
     pub(crate) should_quit: bool,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct ControllerPosition {
     pub left_stick: V2,
     pub right_stick: V2,
@@ -104,6 +106,9 @@ impl<'game> Game<'game> {
             text_textures: Default::default(),
             fonts: Default::default(),
             currently_pressed_keys: Default::default(),
+            repeated_keys: Default::default(),
+            text_input: Default::default(),
+            text_editing: None,
             currently_pressed_mouse_buttons: Default::default(),
             currently_pressed_controller_buttons: Default::default(),
             controllers: Default::default(),
@@ -129,6 +134,8 @@ impl<'game> Game<'game> {
             self.currently_pressed_keys.values_mut().for_each(|value| {
                 *value = false;
             });
+            self.repeated_keys.clear();
+            self.text_input.clear();
             if self.should_quit {
                 break 'running;
             }
@@ -145,12 +152,20 @@ impl<'game> Game<'game> {
                         if !self.currently_pressed_keys.contains_key(&btn) {
                             self.currently_pressed_keys.insert(btn, true);
                         }
+                        self.repeated_keys.push(btn);
                     }
                     Event::KeyUp {
                         keycode: Some(btn), ..
                     } => {
                         self.currently_pressed_keys.remove(&btn);
                     }
+                    Event::TextInput { text, .. } => {
+                        self.text_input.push_str(&text);
+                        self.text_editing = None;
+                    }
+                    Event::TextEditing { text, .. } => {
+                        self.text_editing = (!text.is_empty()).then_some(text);
+                    }
                     Event::MouseButtonDown { mouse_btn: btn, .. } => {
                         self.currently_pressed_mouse_buttons.insert(btn, true);
                     }
@@ -170,15 +185,27 @@ impl<'game> Game<'game> {
                             .remove(&(which.into(), btn));
                     }
                     Event::ControllerDeviceAdded { which, .. } => {
-                        let controller = self.controller_subsystem.open(which).unwrap();
-                        self.controllers
-                            .push((which.into(), controller, Default::default()));
+                        // `which` is the device index here, every other controller event uses the instance id
+                        let controller = match self.controller_subsystem.open(which) {
+                            Ok(controller) => controller,
+                            Err(err) => {
+                                println!("unable to open controller {which}: {err}");
+                                continue;
+                            }
+                        };
+                        let id = controller.instance_id().into();
+                        if self.controllers.iter().any(|v| v.0 == id) {
+                            continue;
+                        }
+                        self.controllers.push((id, controller, Default::default()));
                     }
                     Event::ControllerDeviceRemoved { which, .. } => {
-                        if let Some(pos) = self.controllers.iter().position(|v| v.0 == which.into())
-                        {
+                        let id: Id = which.into();
+                        if let Some(pos) = self.controllers.iter().position(|v| v.0 == id) {
                             self.controllers.remove(pos);
                         };
+                        self.currently_pressed_controller_buttons
+                            .retain(|(controller_id, _), _| *controller_id != id);
                     }
                     Event::ControllerAxisMotion {
                         value, which, axis, ..
@@ -0,0 +1,128 @@
+use std::rc::Rc;
+
+use crate::{query, rigid_body::RigidBody, Component, Context, Error, System, V2};
+
+#[derive(Clone)]
+pub enum Path {
+    /// Moves along the points at a constant speed, either back and forth
+    /// or looping back to the first point.
+    Waypoints {
+        points: Vec<V2>,
+        speed: f64,
+        ping_pong: bool,
+    },
+    /// Position as a function of the time since the path started.
+    Curve(Rc<dyn Fn(f64) -> V2>),
+}
+
+impl Path {
+    pub fn pos_at(&self, time: f64) -> V2 {
+        match self {
+            Path::Waypoints {
+                points,
+                speed,
+                ping_pong,
+            } => waypoints_pos_at(points, speed * time, *ping_pong),
+            Path::Curve(curve) => curve(time),
+        }
+    }
+}
+
+fn waypoints_pos_at(points: &[V2], distance: f64, ping_pong: bool) -> V2 {
+    let Some(first) = points.first() else {
+        return V2::new(0.0, 0.0);
+    };
+    let mut segments = points
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect::<Vec<_>>();
+    if !ping_pong {
+        segments.push((*points.last().unwrap(), *first));
+    }
+    segments.retain(|(p0, p1)| (*p1 - *p0).len() > 0.0);
+    let length = segments
+        .iter()
+        .map(|(p0, p1)| (*p1 - *p0).len())
+        .sum::<f64>();
+    if length <= 0.0 {
+        return *first;
+    }
+
+    let mut distance = if ping_pong {
+        let distance = distance.rem_euclid(length * 2.0);
+        if distance > length {
+            length * 2.0 - distance
+        } else {
+            distance
+        }
+    } else {
+        distance.rem_euclid(length)
+    };
+
+    for (p0, p1) in &segments {
+        let segment_length = (*p1 - *p0).len();
+        if distance <= segment_length {
+            return p0.move_along(*p1 - *p0, distance);
+        }
+        distance -= segment_length;
+    }
+    segments.last().map(|(_, p1)| *p1).unwrap_or(*first)
+}
+
+/// Moves the body along a path by setting its velocity, so collisions see
+/// the motion and bodies resting on top are carried along.
+#[derive(Component, Clone)]
+pub struct Kinematic {
+    pub path: Path,
+    pub time: f64,
+}
+
+impl Kinematic {
+    pub fn new(path: Path) -> Self {
+        Self { path, time: 0.0 }
+    }
+
+    pub fn waypoints<I: IntoIterator<Item = V2>>(points: I, speed: f64, ping_pong: bool) -> Self {
+        Self::new(Path::Waypoints {
+            points: points.into_iter().collect(),
+            speed,
+            ping_pong,
+        })
+    }
+
+    pub fn curve<F: Fn(f64) -> V2 + 'static>(curve: F) -> Self {
+        Self::new(Path::Curve(Rc::new(curve)))
+    }
+}
+
+/// Has to run before the `CollisionSystem` and `VelocitySystem`.
+pub struct KinematicSystem(pub u64);
+impl System for KinematicSystem {
+    fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
+        if delta <= 0.0 {
+            return Ok(());
+        }
+        for id in query!(ctx, RigidBody, Kinematic) {
+            let kinematic = ctx.select::<Kinematic>(id);
+            kinematic.time += delta;
+            let target = kinematic.path.pos_at(kinematic.time);
+            let body = ctx.select::<RigidBody>(id);
+            body.vel = (target - body.pos).div_comps(delta);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn waypoints_path() {
+    let points = [V2::new(0.0, 0.0), V2::new(100.0, 0.0), V2::new(100.0, 50.0)];
+
+    let pos = waypoints_pos_at(&points, 120.0, true);
+    assert_eq!((pos.x, pos.y), (100.0, 20.0));
+    let pos = waypoints_pos_at(&points, 250.0, true);
+    assert_eq!((pos.x, pos.y), (50.0, 0.0));
+    let pos = waypoints_pos_at(&points, 150.0 + 112.0 / 2.0, false);
+    assert!((pos.x - 50.0).abs() < 1.0 && (pos.y - 25.0).abs() < 1.0);
+    let pos = waypoints_pos_at(&[V2::new(3.0, 4.0)], 10.0, false);
+    assert_eq!((pos.x, pos.y), (3.0, 4.0));
+}
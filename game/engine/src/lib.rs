@@ -13,11 +13,16 @@ mod text;
 mod texture;
 
 pub mod collision;
+pub mod kinematic;
 pub mod physics;
+pub mod raycast;
 pub mod rigid_body;
 pub mod shared_ptr;
 pub mod ui;
 
+#[cfg(test)]
+mod tests;
+
 pub use self::{
     collision::CollisionSystem,
     collision::SolidCollider,
@@ -26,7 +31,7 @@ pub use self::{
     context::Context,
     context::DrawTextureOpts,
     error::Error,
-    game::Game,
+    game::{ControllerPosition, Game},
     id::{Id, IdAccumulator},
     physics::clamp,
     physics::max,
@@ -39,6 +44,7 @@ pub use self::{
     texture::Texture,
 };
 pub use component_macro::Component;
+pub use sdl2::controller::Axis as ControllerAxis;
 pub use sdl2::controller::Button as ControllerButton;
 pub use sdl2::keyboard::Keycode;
 pub use sdl2::mouse::MouseButton;
@@ -111,6 +111,23 @@ impl V2 {
         let t = (distance + self.len()) / self.len();
         self.extend(t)
     }
+
+    pub fn dot(&self, rhs: V2) -> f64 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    pub fn cross(&self, rhs: V2) -> f64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    /// Unit vector in the same direction, zero stays zero.
+    pub fn normalized(&self) -> Self {
+        let len = self.len();
+        if len == 0.0 {
+            return *self;
+        }
+        self.div_comps(len)
+    }
 }
 
 impl std::ops::Add for V2 {
@@ -189,42 +206,6 @@ impl Moving<V2> {
         }
     }
 
-    /// Will a moving point (self) pass a static point (p), where p is on the same movement path?
-    pub fn crosses_point_debug(&self, p: V2) -> bool {
-        if self.delta_pos.x == 0.0 && self.delta_pos.y == 0.0 {
-            // no movement; it will never pass
-            return false;
-        }
-        // draw a line from the target position backwards to the point
-        // if the point is on the line, then it intends to pass the point
-
-        // intended_movement = origin->target = (target - origin)
-        // point_to_pass = intended_movement * distance_factor + origin
-        // distance_factor = (point_to_pass - origin) / intended_movement
-        let origin = self.inner;
-        let target = origin + self.delta_pos;
-        let point_to_pass = p;
-        let intended_movement = target - origin;
-        // prevent cringe division by 0
-        let distance_factor = if intended_movement.y == 0.0 {
-            (point_to_pass.x - origin.x) / intended_movement.x
-        } else {
-            (point_to_pass.y - origin.y) / intended_movement.y
-        };
-
-        if distance_factor < -0.01 {
-            // has not yet reached point
-            dbg!(origin, intended_movement, point_to_pass, distance_factor);
-            return false;
-        }
-        if distance_factor > 1.0 {
-            // has already passed point
-            println!("passed T~T");
-            return false;
-        }
-        true
-    }
-
     /// Will a moving point (self) pass a static point (p), where p is on the same movement path?
     pub fn crosses_point(&self, p: V2) -> bool {
         if self.delta_pos.x == 0.0 && self.delta_pos.y == 0.0 {
@@ -276,39 +257,34 @@ impl Moving<V2> {
     /// The close intersection is to point, the closer factor is to zero,
     /// factor is zero, when intersection is at point + delta_pos.
     pub fn line_segment_intersect(&self, line: Line) -> Option<Intersection> {
-        // TODO: uhhhh keep debugging idk
-        let debug = false && line.p0.x == 306.0 && line.p0.y == 162.0 && self.inner.x < 600.0;
-        if debug {
-            println!("started new");
-            if self.inner.x < 306.0 {
-                // panic!();
-            }
-        }
         if self.delta_pos.len() == 0.0 {
             // no movement, no collision
             return None;
         }
-        let intersection = self.line_intersect(line)?;
-        if !line.point_within_segment(intersection) {
+        // solve origin + delta_pos * t = p0 + direction * u, solving for
+        // both factors keeps it exact for axis aligned edges, where solving
+        // for the point and checking it is on the line loses precision
+        let direction = line.direction();
+        let denominator = self.delta_pos.cross(direction);
+        if denominator == 0.0 {
+            // parallel: either none or continous intersection
             return None;
         }
-
-        let cwosses = if debug {
-            self.crosses_point_debug(intersection)
-        } else {
-            self.crosses_point(intersection)
-        };
-
-        if !cwosses {
-            if debug {
-                println!("doth not cwoss point");
-                dbg!(intersection, self.inner.x, self.delta_pos.x);
-            }
+        let to_line = line.p0 - self.inner;
+        let distance_factor = to_line.cross(direction) / denominator;
+        let segment_factor = to_line.cross(self.delta_pos) / denominator;
+        if !(0.0..=1.0).contains(&distance_factor) || !(0.0..=1.0).contains(&segment_factor) {
             return None;
         }
-        let distance_factor = self.distance_factor_to_point(intersection);
+        let mut pos = self.inner + self.delta_pos.extend(distance_factor);
+        if direction.x == 0.0 {
+            pos.x = line.p0.x;
+        }
+        if direction.y == 0.0 {
+            pos.y = line.p0.y;
+        }
         Some(Intersection {
-            pos: intersection,
+            pos,
             distance_factor,
         })
     }
@@ -368,6 +344,118 @@ impl Rect {
     }
 }
 
+/// Convex polygon, points in clockwise order in screen coordinates.
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    pub points: Vec<V2>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<V2>) -> Self {
+        Self { points }
+    }
+
+    pub fn from_rect(rect: Rect) -> Self {
+        Self::new(vec![
+            rect.top_left(),
+            rect.top_right(),
+            rect.bottom_right(),
+            rect.bottom_left(),
+        ])
+    }
+
+    pub fn translated(&self, offset: V2) -> Self {
+        Self::new(self.points.iter().map(|p| *p + offset).collect())
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Line> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points
+            .iter()
+            .zip(next)
+            .map(|(p0, p1)| Line::new(*p0, *p1))
+    }
+
+    /// Outward unit normals of the edges.
+    pub fn normals(&self) -> impl Iterator<Item = V2> + '_ {
+        self.edges()
+            .map(|line| line.direction())
+            .filter(|dir| dir.len() > 0.0)
+            .map(|dir| V2::new(dir.y, -dir.x).normalized())
+    }
+
+    fn center(&self) -> V2 {
+        let sum = self.points.iter().fold(V2::default(), |acc, p| acc + *p);
+        sum.div_comps(self.points.len() as f64)
+    }
+
+    fn project(&self, axis: V2) -> (f64, f64) {
+        self.points
+            .iter()
+            .map(|p| p.dot(axis))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), d| {
+                (min(lo, d), max(hi, d))
+            })
+    }
+
+    /// Separating axis test. Returns the unit normal pointing away from
+    /// `other` and the depth to move self along it to stop overlapping.
+    pub fn penetration(&self, other: &Polygon) -> Option<(V2, f64)> {
+        let mut least: Option<(V2, f64)> = None;
+        for axis in self.normals().chain(other.normals()) {
+            let (lo, hi) = self.project(axis);
+            let (other_lo, other_hi) = other.project(axis);
+            let depth = min(hi, other_hi) - max(lo, other_lo);
+            if depth <= 0.0 {
+                return None;
+            }
+            if least.is_none_or(|(_, least_depth)| depth < least_depth) {
+                least = Some((axis, depth));
+            }
+        }
+        let (axis, depth) = least?;
+        let away = self.center() - other.center();
+        if away.dot(axis) < 0.0 {
+            Some((axis.reverse(), depth))
+        } else {
+            Some((axis, depth))
+        }
+    }
+    /// Swept separating axis test of self moving by `motion` against `other`.
+    /// Returns the fraction of the motion until they touch, zero if they
+    /// already overlap, and the normal of the surface hit.
+    pub fn sweep(&self, motion: V2, other: &Polygon) -> Option<(f64, V2)> {
+        let mut enter = f64::NEG_INFINITY;
+        let mut exit = f64::INFINITY;
+        let mut normal = None;
+        for axis in self.normals().chain(other.normals()) {
+            let (lo, hi) = self.project(axis);
+            let (other_lo, other_hi) = other.project(axis);
+            let speed = motion.dot(axis);
+            if speed == 0.0 {
+                if hi <= other_lo || other_hi <= lo {
+                    return None;
+                }
+                continue;
+            }
+            let (t0, t1) = ((other_lo - hi) / speed, (other_hi - lo) / speed);
+            let (t0, t1) = (min(t0, t1), max(t0, t1));
+            if t0 > enter {
+                enter = t0;
+                normal = Some(if speed > 0.0 { axis.reverse() } else { axis });
+            }
+            exit = min(exit, t1);
+            if enter > exit {
+                return None;
+            }
+        }
+        if exit < 0.0 || enter > 1.0 {
+            return None;
+        }
+        Some((max(enter, 0.0), normal?))
+    }
+}
+
 impl Moving<Rect> {
     pub fn rect_within_reach(&self, other: Rect) -> bool {
         let radii = self.radius() + self.delta_pos.len() + other.radius();
@@ -430,6 +518,17 @@ pub enum QuadDirection {
 }
 
 impl QuadDirection {
+    /// Unit vector pointing out of this side.
+    pub fn normal(&self) -> V2 {
+        use QuadDirection::*;
+        match self {
+            Top => V2::new(0.0, -1.0),
+            Right => V2::new(1.0, 0.0),
+            Bottom => V2::new(0.0, 1.0),
+            Left => V2::new(-1.0, 0.0),
+        }
+    }
+
     pub fn reverse(&self) -> QuadDirection {
         use QuadDirection::*;
         match self {
@@ -469,6 +568,19 @@ impl OctoDirection {
             (Equal, Equal) => None,
         }
     }
+    /// The closest of the eight directions to a vector, e.g. a surface normal.
+    pub fn nearest(value: V2) -> Option<Self> {
+        let threshold = (std::f64::consts::PI / 8.0).sin() * value.len();
+        let snap = |v: f64| {
+            if v.abs() < threshold {
+                0.0
+            } else {
+                v
+            }
+        };
+        Self::from_v2(V2::new(snap(value.x), snap(value.y)))
+    }
+
     pub fn reverse(&self) -> Self {
         use OctoDirection::*;
         match self {
@@ -534,3 +646,43 @@ impl From<QuadDirection> for OctoDirection {
         }
     }
 }
+
+#[test]
+fn polygon_penetration() {
+    let ground = Polygon::from_rect(Rect::from_f64(0.0, 100.0, 200.0, 50.0));
+    let slope = Polygon::new(vec![
+        V2::new(0.0, 100.0),
+        V2::new(100.0, 0.0),
+        V2::new(100.0, 100.0),
+    ]);
+
+    let resting = Polygon::from_rect(Rect::from_f64(50.0, 60.0, 20.0, 50.0));
+    let (normal, depth) = resting.penetration(&ground).unwrap();
+    assert_eq!((normal.x, normal.y, depth), (0.0, -1.0, 10.0));
+
+    let on_slope = Polygon::from_rect(Rect::from_f64(60.0, 20.0, 20.0, 20.0));
+    let (normal, depth) = on_slope.penetration(&slope).unwrap();
+    assert!((normal.x + normal.y.abs()).abs() < 1e-9 && normal.y < 0.0);
+    assert!((depth - 20.0 / 2f64.sqrt()).abs() < 1e-9);
+
+    let above = Polygon::from_rect(Rect::from_f64(0.0, 0.0, 20.0, 20.0));
+    assert!(above.penetration(&slope).is_none());
+}
+
+#[test]
+fn polygon_sweep() {
+    let ground = Polygon::from_rect(Rect::from_f64(0.0, 100.0, 200.0, 50.0));
+
+    let ray = Polygon::new(vec![V2::new(50.0, 0.0)]);
+    let (t, normal) = ray.sweep(V2::new(0.0, 200.0), &ground).unwrap();
+    assert_eq!((t, normal.x, normal.y), (0.5, 0.0, -1.0));
+    assert!(ray.sweep(V2::new(0.0, 50.0), &ground).is_none());
+    assert!(ray.sweep(V2::new(300.0, 0.0), &ground).is_none());
+
+    let body = Polygon::from_rect(Rect::from_f64(-100.0, 110.0, 20.0, 20.0));
+    let (t, normal) = body.sweep(V2::new(200.0, 0.0), &ground).unwrap();
+    assert_eq!((t, normal.x, normal.y), (0.4, -1.0, 0.0));
+
+    let inside = Polygon::from_rect(Rect::from_f64(10.0, 110.0, 20.0, 20.0));
+    assert_eq!(inside.sweep(V2::new(0.0, 10.0), &ground).unwrap().0, 0.0);
+}
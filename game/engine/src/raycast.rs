@@ -0,0 +1,241 @@
+use crate::{
+    collision::{rects_collide, ColliderGrids, PolygonCollider, ShallowCollider, ALL_LAYERS},
+    max, min,
+    physics::{OctoDirection, Polygon, QuadDirection, Rect},
+    query,
+    rigid_body::RigidBody,
+    Context, Id, SolidCollider, V2,
+};
+
+/// Which colliders a query can hit.
+#[derive(Clone)]
+pub struct QueryFilter {
+    pub mask: u32,
+    pub exclude: Vec<Id>,
+    pub shallow: bool,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryFilter {
+    pub fn new() -> Self {
+        Self {
+            mask: ALL_LAYERS,
+            exclude: Vec::new(),
+            shallow: true,
+        }
+    }
+
+    pub fn mask(self, mask: u32) -> Self {
+        Self { mask, ..self }
+    }
+
+    /// Skips the entity, e.g. the one casting.
+    pub fn excluding(mut self, id: Id) -> Self {
+        self.exclude.push(id);
+        self
+    }
+
+    pub fn without_shallow(self) -> Self {
+        Self {
+            shallow: false,
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CastHit {
+    pub id: Id,
+    /// Where the ray hit, or the position of the swept shape when it hit.
+    pub pos: V2,
+    pub distance: f64,
+    /// Normal of the surface hit, pointing away from it.
+    pub normal: V2,
+}
+
+enum Shape {
+    Solid,
+    Shallow(ShallowCollider),
+    Polygon(Polygon),
+}
+
+struct Target {
+    id: Id,
+    bounds: Rect,
+    shape: Shape,
+}
+
+impl Target {
+    fn polygon(&self) -> Polygon {
+        match &self.shape {
+            Shape::Polygon(polygon) => polygon.clone(),
+            Shape::Solid | Shape::Shallow(_) => Polygon::from_rect(self.bounds),
+        }
+    }
+}
+
+/// Ids of the solid, shallow and polygon colliders that may be in the
+/// area. Uses the grids of the last collision update, despawned colliders
+/// are left out and every collider is a candidate before the first update.
+fn candidates(ctx: &mut Context, pos: V2, size: V2) -> [Vec<Id>; 3] {
+    let all = [
+        query!(ctx, RigidBody, SolidCollider),
+        query!(ctx, RigidBody, ShallowCollider),
+        query!(ctx, RigidBody, PolygonCollider),
+    ];
+    let Some(grids_id) = query!(ctx, ColliderGrids).first().copied() else {
+        return all;
+    };
+    let grids = ctx.select::<ColliderGrids>(grids_id);
+    let mut candidates = [
+        grids.solids.query(pos, size),
+        grids.shallows.query(pos, size),
+        grids.polygons.query(pos, size),
+    ];
+    for (ids, all) in candidates.iter_mut().zip(all) {
+        ids.retain(|id| all.contains(id));
+    }
+    candidates
+}
+
+/// Solid, shallow and polygon colliders in the area the filter lets
+/// through.
+fn targets(ctx: &mut Context, filter: &QueryFilter, pos: V2, size: V2) -> Vec<Target> {
+    let [solids, shallows, polygons] = candidates(ctx, pos, size);
+    let mut targets = Vec::new();
+    for id in solids {
+        if ctx.select::<SolidCollider>(id).layer & filter.mask == 0 {
+            continue;
+        }
+        let body = ctx.select::<RigidBody>(id);
+        let bounds = Rect::new(body.pos, body.size);
+        let shape = Shape::Solid;
+        targets.push(Target { id, bounds, shape });
+    }
+    if filter.shallow {
+        for id in shallows {
+            let collider = ctx.select::<ShallowCollider>(id).clone();
+            if collider.layer & filter.mask == 0 {
+                continue;
+            }
+            let body = ctx.select::<RigidBody>(id);
+            let bounds = Rect::new(body.pos, body.size);
+            let shape = Shape::Shallow(collider);
+            targets.push(Target { id, bounds, shape });
+        }
+    }
+    for id in polygons {
+        let body = ctx.select::<RigidBody>(id).clone();
+        let collider = ctx.select::<PolygonCollider>(id);
+        if collider.layer & filter.mask == 0 {
+            continue;
+        }
+        let (pos, size) = collider.bounds(&body);
+        let bounds = Rect::new(pos, size);
+        let shape = Shape::Polygon(collider.polygon(&body));
+        targets.push(Target { id, bounds, shape });
+    }
+    targets.retain(|target| !filter.exclude.contains(&target.id));
+    targets
+}
+
+/// Moves the shape along `motion` and returns the first collider it hits.
+/// Shallow colliders are only hit from the sides they collide on.
+fn cast(ctx: &mut Context, shape: Polygon, motion: V2, filter: &QueryFilter) -> Option<CastHit> {
+    if motion.len() == 0.0 {
+        return None;
+    }
+    let (lo, hi) = shape.points.iter().fold(
+        (
+            V2::new(f64::INFINITY, f64::INFINITY),
+            V2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(lo, hi), p| {
+            (
+                V2::new(min(lo.x, p.x), min(lo.y, p.y)),
+                V2::new(max(hi.x, p.x), max(hi.y, p.y)),
+            )
+        },
+    );
+    // bounds of the whole motion, with a margin for touching colliders
+    let margin = V2::new(1.0, 1.0);
+    let swept_pos = V2::new(min(lo.x, lo.x + motion.x), min(lo.y, lo.y + motion.y)) - margin;
+    let swept_size = hi - lo + V2::new(motion.x.abs(), motion.y.abs()) + margin.extend(2.0);
+
+    let mut closest: Option<(f64, Id, V2)> = None;
+    for target in targets(ctx, filter, swept_pos, swept_size) {
+        let bounds = target.bounds;
+        if !rects_collide(swept_pos, swept_size, bounds.pos, bounds.size) {
+            continue;
+        }
+        let polygon = target.polygon();
+        let Some((t, normal)) = shape.sweep(motion, &polygon) else {
+            continue;
+        };
+        if let Shape::Shallow(collider) = &target.shape {
+            let side = OctoDirection::from_v2(normal).and_then(|dir| dir.try_into().ok());
+            let from_side = side.is_some_and(|side: QuadDirection| collider.collides_on(side));
+            if !from_side || shape.penetration(&polygon).is_some() {
+                continue;
+            }
+        }
+        if closest.is_none_or(|(closest_t, _, _)| t < closest_t) {
+            closest = Some((t, target.id, normal));
+        }
+    }
+    let (t, id, normal) = closest?;
+    Some(CastHit {
+        id,
+        pos: lo + motion.extend(t),
+        distance: motion.len() * t,
+        normal,
+    })
+}
+
+/// First collider hit by a ray from `origin` towards `direction`.
+pub fn raycast(
+    ctx: &mut Context,
+    origin: V2,
+    direction: V2,
+    max_distance: f64,
+    filter: &QueryFilter,
+) -> Option<CastHit> {
+    let motion = direction.normalized().extend(max_distance);
+    cast(ctx, Polygon::new(vec![origin]), motion, filter)
+}
+
+/// First collider hit by a box moved from `pos` by `motion`. The hit
+/// position is where the box stops.
+pub fn sweep(
+    ctx: &mut Context,
+    pos: V2,
+    size: V2,
+    motion: V2,
+    filter: &QueryFilter,
+) -> Option<CastHit> {
+    let shape = Polygon::from_rect(Rect::new(pos, size));
+    cast(ctx, shape, motion, filter)
+}
+
+/// Colliders overlapping the box.
+pub fn overlap(ctx: &mut Context, pos: V2, size: V2, filter: &QueryFilter) -> Vec<Id> {
+    let shape = Polygon::from_rect(Rect::new(pos, size));
+    let mut ids = targets(ctx, filter, pos, size)
+        .into_iter()
+        .filter(|target| match &target.shape {
+            Shape::Polygon(polygon) => shape.penetration(polygon).is_some(),
+            Shape::Solid | Shape::Shallow(_) => {
+                rects_collide(pos, size, target.bounds.pos, target.bounds.size)
+            }
+        })
+        .map(|target| target.id)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
@@ -1,5 +1,73 @@
 use super::{Context, Error, System};
-use crate::{query, rigid_body, Component, V2};
+use crate::{physics::OctoDirection, query, rigid_body, Component, SolidCollider, V2};
+
+/// World wide physics constants, spawn one to override the defaults.
+#[derive(Component, Clone, Debug)]
+pub struct PhysicsConfig {
+    pub gravity: V2,
+    /// Speed along gravity past which bodies stop accelerating.
+    pub terminal_velocity: f64,
+    pub air_friction: f64,
+    /// Used for bodies standing on a solid collider.
+    pub ground_friction: f64,
+    /// Speed of jumps, against gravity.
+    pub jump_speed: f64,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PhysicsConfig {
+    pub fn new() -> Self {
+        Self {
+            gravity: V2::new(0.0, 1600.0),
+            terminal_velocity: 400.0,
+            air_friction: 0.1,
+            ground_friction: 0.1,
+            jump_speed: 800.0,
+        }
+    }
+
+    /// The spawned config, or the defaults if there is none.
+    pub fn current(ctx: &mut Context) -> Self {
+        match query!(ctx, PhysicsConfig).first() {
+            Some(id) => ctx.select::<PhysicsConfig>(*id).clone(),
+            None => Self::new(),
+        }
+    }
+
+    pub fn gravity(self, gravity: V2) -> Self {
+        Self { gravity, ..self }
+    }
+
+    pub fn terminal_velocity(self, terminal_velocity: f64) -> Self {
+        Self {
+            terminal_velocity,
+            ..self
+        }
+    }
+
+    pub fn air_friction(self, air_friction: f64) -> Self {
+        Self {
+            air_friction,
+            ..self
+        }
+    }
+
+    pub fn ground_friction(self, ground_friction: f64) -> Self {
+        Self {
+            ground_friction,
+            ..self
+        }
+    }
+
+    pub fn jump_speed(self, jump_speed: f64) -> Self {
+        Self { jump_speed, ..self }
+    }
+}
 
 #[derive(Component, Clone, Debug)]
 pub struct RigidBody {
@@ -8,6 +76,10 @@ pub struct RigidBody {
     pub size: V2,
     pub gravity: bool,
     pub drag: bool,
+    /// Scales both the gravity and the terminal velocity of this body.
+    pub gravity_scale: f64,
+    /// Scales the friction of this body.
+    pub drag_scale: f64,
 }
 
 impl RigidBody {
@@ -18,6 +90,8 @@ impl RigidBody {
             size: V2::new(0.0, 0.0),
             gravity: false,
             drag: false,
+            gravity_scale: 1.0,
+            drag_scale: 1.0,
         }
     }
 
@@ -43,6 +117,17 @@ impl RigidBody {
     pub fn with_drag(self) -> Self {
         Self { drag: true, ..self }
     }
+
+    pub fn with_gravity_scale(self, gravity_scale: f64) -> Self {
+        Self {
+            gravity_scale,
+            ..self
+        }
+    }
+
+    pub fn with_drag_scale(self, drag_scale: f64) -> Self {
+        Self { drag_scale, ..self }
+    }
 }
 
 pub struct VelocitySystem(pub u64);
@@ -59,16 +144,16 @@ impl System for VelocitySystem {
 pub struct GravitySystem(pub u64);
 impl System for GravitySystem {
     fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
+        let config = PhysicsConfig::current(ctx);
+        let down = config.gravity.normalized();
         for id in query!(ctx, RigidBody) {
             let body = ctx.select::<RigidBody>(id);
             if !body.gravity {
                 continue;
             }
-            body.vel.y = if body.vel.y < 400.0 {
-                body.vel.y + 1600.0 * delta
-            } else {
-                body.vel.y
-            };
+            if body.vel.dot(down) < config.terminal_velocity * body.gravity_scale {
+                body.vel += config.gravity.extend(body.gravity_scale * delta);
+            }
         }
         Ok(())
     }
@@ -77,7 +162,19 @@ impl System for GravitySystem {
 pub struct DragSystem(pub u64);
 impl System for DragSystem {
     fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
+        let config = PhysicsConfig::current(ctx);
+        let colliders = query!(ctx, RigidBody, SolidCollider);
         for id in query!(ctx, RigidBody) {
+            let on_ground = colliders.contains(&id)
+                && ctx
+                    .select::<SolidCollider>(id)
+                    .colliding
+                    .is_some_and(|dir| dir.facing(OctoDirection::Bottom));
+            let friction = if on_ground {
+                config.ground_friction
+            } else {
+                config.air_friction
+            };
             let body = ctx.select::<RigidBody>(id);
             if !body.drag {
                 continue;
@@ -85,7 +182,7 @@ impl System for DragSystem {
             if body.vel.x == 0.0 {
                 continue;
             }
-            let eq = body.vel.x.abs().powf(1.25) * delta * 0.1 + 5.0;
+            let eq = body.vel.x.abs().powf(1.25) * delta * friction * body.drag_scale + 5.0;
             if body.vel.x > 10.0 {
                 body.vel.x -= eq;
                 if body.vel.x < 0.0 {
@@ -0,0 +1,107 @@
+use proptest::prelude::*;
+
+use crate::{
+    collision::{collide_with_static, find_collisions, rects_collide, DefaultResolver},
+    physics::QuadDirection,
+    rigid_body::RigidBody,
+    V2,
+};
+
+fn body(pos: V2, size: V2, vel: V2) -> RigidBody {
+    RigidBody::new().with_pos(pos).with_size(size).with_vel(vel)
+}
+
+/// One `CollisionSystem` and `VelocitySystem` step against static bodies.
+fn step(body: &mut RigidBody, others: &[RigidBody], delta: f64) -> Vec<QuadDirection> {
+    let directions = collide_with_static(body, others, &DefaultResolver, delta);
+    body.pos += body.vel.extend(delta);
+    directions
+}
+
+#[test]
+fn lands_on_ground() {
+    let ground = body(V2::new(0.0, 500.0), V2::new(1000.0, 100.0), V2::default());
+    let mut falling = body(
+        V2::new(100.0, 380.0),
+        V2::new(50.0, 100.0),
+        V2::new(0.0, 1200.0),
+    );
+    let directions = step(&mut falling, &[ground], 1.0 / 60.0);
+    assert_eq!(directions, vec![QuadDirection::Bottom]);
+    assert_eq!(falling.pos.y, 400.0);
+    assert_eq!(falling.vel.y, 0.0);
+}
+
+proptest! {
+    #[test]
+    fn no_tunneling_through_ground(
+        w in 10.0..150.0,
+        h in 10.0..150.0,
+        crossing_x in 1.0..849.0,
+        crossed_at in 0.0..1.0,
+        vel_x in -10000.0..10000.0,
+        vel_y in 1.0..100000.0,
+        delta in 0.001..0.1,
+    ) {
+        // the motion crosses the top of the ground away from its corners,
+        // `crossed_at` along the way
+        let ground_top = 500.0;
+        let ground = body(V2::new(0.0, ground_top), V2::new(1000.0, 100.0), V2::default());
+        let vel = V2::new(vel_x, vel_y);
+        let delta_pos = vel.extend(delta * crossed_at);
+        let pos = V2::new(crossing_x, ground_top - h) - delta_pos;
+
+        let mut falling = body(pos, V2::new(w, h), vel);
+        step(&mut falling, std::slice::from_ref(&ground), delta);
+        prop_assert!(falling.pos.y + h <= ground_top + 1e-6, "fell to {:?}", falling.pos);
+        prop_assert!(!rects_collide(falling.pos, falling.size, ground.pos, ground.size));
+    }
+
+    #[test]
+    fn collisions_are_symmetric(
+        pos in (-500.0..500.0, -500.0..500.0),
+        size in (10.0..200.0, 10.0..200.0),
+        other_pos in (-500.0..500.0, -500.0..500.0),
+        other_size in (10.0..200.0, 10.0..200.0),
+        vel in (-5000.0..5000.0, -5000.0..5000.0),
+    ) {
+        let delta = 1.0 / 60.0;
+        let moving = body(pos.into(), size.into(), vel.into());
+        let still = body(other_pos.into(), other_size.into(), V2::default());
+        prop_assume!(!rects_collide(moving.pos, moving.size, still.pos, still.size));
+
+        let mut forward = Vec::new();
+        find_collisions(&mut forward, &moving, &still, delta, |_, _| true);
+
+        // the same motion, seen from the other body
+        let moving_back = body(still.pos, still.size, moving.vel.reverse());
+        let still_back = body(moving.pos, moving.size, V2::default());
+        let mut backward = Vec::new();
+        find_collisions(&mut backward, &moving_back, &still_back, delta, |_, _| true);
+
+        prop_assert_eq!(forward.is_empty(), backward.is_empty());
+    }
+
+    #[test]
+    fn resolved_bodies_do_not_overlap(
+        pos in (-500.0..500.0, -500.0..500.0),
+        size in (10.0..200.0, 10.0..200.0),
+        other_pos in (-500.0..500.0, -500.0..500.0),
+        other_size in (10.0..200.0, 10.0..200.0),
+        vel in (-50000.0..50000.0, -50000.0..50000.0),
+    ) {
+        let delta = 1.0 / 60.0;
+        let mut moving = body(pos.into(), size.into(), vel.into());
+        let still = body(other_pos.into(), other_size.into(), V2::default());
+        prop_assume!(!rects_collide(moving.pos, moving.size, still.pos, still.size));
+
+        step(&mut moving, std::slice::from_ref(&still), delta);
+        // ending up exactly against the other body may round into it
+        let margin = V2::new(1e-6, 1e-6);
+        prop_assert!(
+            !rects_collide(moving.pos + margin, moving.size - margin.extend(2.0), still.pos, still.size),
+            "ended at {:?}",
+            moving.pos
+        );
+    }
+}
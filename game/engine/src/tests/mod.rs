@@ -0,0 +1,2 @@
+mod collision;
+mod physics;
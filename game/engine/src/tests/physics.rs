@@ -0,0 +1,167 @@
+use proptest::prelude::*;
+
+use crate::physics::*;
+
+#[test]
+fn test_v2() {
+    assert_eq!(
+        V2::new(3.0, 3.0).move_along(V2::new(3.0, 0.0), 20.0),
+        V2::new(3.0 + 20.0, 3.0)
+    );
+    assert_eq!(
+        V2::new(3.0, 0.0).extend_distance(4.0),
+        V2::new(3.0 + 4.0, 0.0)
+    );
+}
+
+#[test]
+fn test_rects_within_reach() {
+    assert!(Rect::from_f64(0.0, 0.0, 10.0, 0.0)
+        .moving(V2::new(10.0, 10.0))
+        .rect_within_reach(Rect::from_f64(15.0, 0.0, 10.0, 10.0)));
+    assert!(!Rect::from_f64(0.0, 0.0, 10.0, 0.0)
+        .moving(V2::new(10.0, 10.0))
+        .rect_within_reach(Rect::from_f64(40.0, 0.0, 10.0, 10.0)));
+}
+
+#[test]
+fn test_point_vec_line_segment_intersect() {
+    macro_rules! named {
+        ($name: ident) => {
+            (stringify!($name), $name)
+        };
+    }
+    let check_a = {
+        let edge_a = (V2::new(10.0, 10.0), V2::new(40.0, 10.0));
+        let line_a = (V2::new(20.0, 0.0), V2::new(10.0, 20.0));
+        let line_b = (V2::new(25.0, 0.0), V2::new(0.0, 25.0));
+        let line_c = (V2::new(30.0, 0.0), V2::new(-10.0, 20.0));
+        let intersection = V2::new(25.0, 10.0);
+
+        [named!(line_a), named!(line_b), named!(line_c)]
+            .into_iter()
+            .map(|line| (line, named!(edge_a), intersection))
+            .collect::<Vec<_>>()
+    };
+    let check_b = {
+        let edge_b = (V2::new(40.0, 40.0), V2::new(40.0, 10.0));
+        let line_d = (V2::new(50.0, 20.0), V2::new(-20.0, 10.0));
+        let line_e = (V2::new(50.0, 25.0), V2::new(-25.0, 0.0));
+        let line_f = (V2::new(50.0, 30.0), V2::new(-20.0, -10.0));
+        let intersection = V2::new(40.0, 25.0);
+
+        [named!(line_d), named!(line_e), named!(line_f)]
+            .into_iter()
+            .map(|line| (line, named!(edge_b), intersection))
+            .collect::<Vec<_>>()
+    };
+    let check_c = {
+        let edge_c = (V2::new(40.0, 40.0), V2::new(10.0, 40.0));
+        let line_i = (V2::new(20.0, 50.0), V2::new(10.0, -20.0));
+        let line_h = (V2::new(25.0, 50.0), V2::new(0.0, -25.0));
+        let line_g = (V2::new(30.0, 50.0), V2::new(-10.0, -20.0));
+        let intersection = V2::new(25.0, 40.0);
+
+        [named!(line_i), named!(line_h), named!(line_g)]
+            .into_iter()
+            .map(|line| (line, named!(edge_c), intersection))
+            .collect::<Vec<_>>()
+    };
+    let check_d = {
+        let edge_d = (V2::new(10.0, 10.0), V2::new(10.0, 40.0));
+        let line_d = (V2::new(0.0, 20.0), V2::new(20.0, 10.0));
+        let line_e = (V2::new(0.0, 25.0), V2::new(25.0, 0.0));
+        let line_f = (V2::new(0.0, 30.0), V2::new(20.0, -10.0));
+        let intersection = V2::new(10.0, 25.0);
+
+        [named!(line_d), named!(line_e), named!(line_f)]
+            .into_iter()
+            .map(|line| (line, named!(edge_d), intersection))
+            .collect::<Vec<_>>()
+    };
+    [check_a, check_b, check_c, check_d]
+        .into_iter()
+        .flatten()
+        .for_each(
+            |(
+                (line_name, (pos, delta_pos)),
+                (edge_name, (edge_p0, edge_p1)),
+                expected_intersection,
+            )| {
+                let intersection = pos
+                    .moving(delta_pos)
+                    .line_segment_intersect(Line::new(edge_p0, edge_p1))
+                    .map(|Intersection { pos, distance_factor: _ }| pos);
+
+                assert!(
+                    intersection.is_some(),
+                    "expected line {line_name} to intersect with edge {edge_name}, got None"
+                );
+
+                let intersection = intersection.expect("we asserted it to be Some");
+                assert_eq!(intersection, expected_intersection, "expected line {line_name} to intersect with edge {edge_name} at {expected_intersection:?}, got {intersection:?}")
+            },
+        );
+}
+
+fn coord() -> impl Strategy<Value = f64> {
+    -2000.0..2000.0
+}
+
+fn v2() -> impl Strategy<Value = V2> {
+    (coord(), coord()).prop_map(|(x, y)| V2::new(x, y))
+}
+
+proptest! {
+    #[test]
+    fn crossing_a_segment_always_intersects(
+        x0 in 0.0..1000.0,
+        y0 in -2000.0..-1.0,
+        x1 in 0.0..1000.0,
+        y1 in 1.0..2000.0,
+        edge_y in coord(),
+    ) {
+        // starts above the horizontal edge and ends below it, within its span
+        let origin = V2::new(x0, y0 + edge_y);
+        let delta_pos = V2::new(x1 - x0, y1 - y0);
+        let edge = Line::new(V2::new(0.0, edge_y), V2::new(1000.0, edge_y));
+        let intersection = origin.moving(delta_pos).line_segment_intersect(edge);
+        prop_assert!(intersection.is_some());
+        let Intersection { pos, distance_factor } = intersection.unwrap();
+        prop_assert_eq!(pos.y, edge_y);
+        prop_assert!((0.0..=1.0).contains(&distance_factor));
+    }
+
+    #[test]
+    fn intersection_ignores_segment_direction(
+        origin in v2(),
+        delta_pos in v2(),
+        p0 in v2(),
+        p1 in v2(),
+    ) {
+        let moving = origin.moving(delta_pos);
+        let forward = moving.line_segment_intersect(Line::new(p0, p1));
+        let backward = moving.line_segment_intersect(Line::new(p1, p0));
+        prop_assert_eq!(forward.is_some(), backward.is_some());
+        if let (Some(forward), Some(backward)) = (forward, backward) {
+            prop_assert!((forward.distance_factor - backward.distance_factor).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn intersection_lies_on_the_motion(
+        origin in v2(),
+        delta_pos in v2(),
+        p0 in v2(),
+        p1 in v2(),
+    ) {
+        let moving = origin.moving(delta_pos);
+        if let Some(Intersection { pos, distance_factor }) =
+            moving.line_segment_intersect(Line::new(p0, p1))
+        {
+            prop_assert!((0.0..=1.0).contains(&distance_factor));
+            let expected = origin + delta_pos.extend(distance_factor);
+            prop_assert!((expected - pos).len() < 1e-6 * (1.0 + delta_pos.len()));
+        }
+    }
+}
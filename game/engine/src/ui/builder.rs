@@ -1,4 +1,4 @@
-use super::{Dom, EventId, InternalNodeId, NodeId};
+use super::{text_input::TextInputState, Dom, EventId, InternalNodeId, NodeId};
 use std::{
     boxed::Box as InnerBox,
     ops::{Deref, DerefMut},
@@ -36,6 +36,7 @@ pub enum Kind {
     Vert(Vec<Box<Node>>),
     Hori(Vec<Box<Node>>),
     Text(String),
+    TextInput { text: String, placeholder: String },
     Image(PathBuf),
     Stack(Vec<Box<Node>>),
 }
@@ -58,6 +59,13 @@ pub mod constructors {
     pub fn Text<S: Into<String>>(text: S) -> Box<Node> {
         Kind::Text(text.into()).into()
     }
+    pub fn TextInput<S: Into<String>>(text: S) -> Box<Node> {
+        Kind::TextInput {
+            text: text.into(),
+            placeholder: String::new(),
+        }
+        .into()
+    }
     pub fn Image<P: Into<PathBuf>>(path: P) -> Box<Node> {
         Kind::Image(path.into()).into()
     }
@@ -162,6 +170,10 @@ impl Node {
                 // font: PathBuf::from("assets/ttf/OpenSans.ttf"),
                 font: PathBuf::from("assets/ttf/Jaro-Regular.ttf"),
             },
+            Kind::TextInput { text, placeholder } => super::Kind::TextInput {
+                state: TextInputState::new(text.clone()).with_placeholder(placeholder.clone()),
+                font: PathBuf::from("assets/ttf/Jaro-Regular.ttf"),
+            },
             Kind::Image(src) => super::Kind::Image(src.clone()),
         };
         nodes.push((
@@ -224,6 +236,17 @@ impl Box<Node> {
         self.on_click = Some(id.into());
         self
     }
+
+    pub fn placeholder<S: Into<String>>(mut self, text: S) -> Self {
+        match self.kind {
+            Kind::TextInput {
+                ref mut placeholder,
+                ..
+            } => *placeholder = text.into(),
+            _ => println!("ui warning: tried to set placeholder of non-text-input node"),
+        }
+        self
+    }
 }
 
 impl From<Kind> for Box<Node> {
@@ -66,6 +66,10 @@ impl Focus {
         }
     }
     pub fn update(&mut self, dom: &mut ui::Dom, ctx: &mut crate::Context) {
+        // keys belong to the text input while editing
+        if dom.editing_text() {
+            return;
+        }
         let gamepad_next = ctx.active_controllers().any(|id| {
             ctx.controller_button_just_pressed(id, crate::ControllerButton::DPadRight)
                 || ctx.controller_button_just_pressed(id, crate::ControllerButton::DPadDown)
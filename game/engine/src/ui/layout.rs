@@ -1,6 +1,10 @@
 use crate::{max, V2};
 
-use super::{ui_context::UiContext, Dom, EventId, InternalNodeId, Kind, Node};
+use std::path::Path;
+
+use super::{
+    text_input::TextInputState, ui_context::UiContext, Dom, EventId, InternalNodeId, Kind, Node,
+};
 
 #[derive(Debug, PartialEq)]
 pub(super) struct LayoutTreeLeaf<'a> {
@@ -29,6 +33,17 @@ impl LayoutTree<'_> {
         }
     }
 
+    pub fn resolve_text_input(&self, mouse_pos: V2) -> Option<InternalNodeId> {
+        match self {
+            LayoutTree::Single(leaf) => leaf.resolve_text_input(mouse_pos),
+            LayoutTree::Multiple(leaf, children) => children
+                .iter()
+                .rev()
+                .find_map(|tree| tree.resolve_text_input(mouse_pos))
+                .or_else(|| leaf.resolve_text_input(mouse_pos)),
+        }
+    }
+
     pub fn resolve_click(&self, mouse_pos: V2) -> Option<(EventId, InternalNodeId)> {
         match self {
             LayoutTree::Single(leaf) => leaf.resolve_click(mouse_pos),
@@ -103,13 +118,86 @@ impl LayoutTreeLeaf<'_> {
 
         let event_id = self.inner.on_click?;
 
-        if !((self.pos.x..self.pos.x + self.size.x).contains(&mouse_position.x)
-            && (self.pos.y..self.pos.y + self.size.y).contains(&mouse_position.y))
-        {
+        if !self.contains(mouse_position) {
             return None;
         }
         Some((event_id, self.node_id))
     }
+
+    fn contains(&self, pos: V2) -> bool {
+        (self.pos.x..self.pos.x + self.size.x).contains(&pos.x)
+            && (self.pos.y..self.pos.y + self.size.y).contains(&pos.y)
+    }
+
+    pub fn resolve_text_input(&self, mouse_position: V2) -> Option<InternalNodeId> {
+        if !self.inner.visible
+            || !matches!(self.inner.kind, Kind::TextInput { .. })
+            || !self.contains(mouse_position)
+        {
+            return None;
+        }
+        Some(self.node_id)
+    }
+
+    fn draw_text_input(&self, ctx: &mut impl UiContext, state: &TextInputState, font: &Path) {
+        let font_size = self.inner.font_size.unwrap_or(15);
+        let font_id = ctx.load_font(font, font_size).unwrap();
+        let color = self.inner.color.unwrap_or((255, 255, 255));
+        let offset = self.inner.padding.unwrap_or(0.0) + self.inner.border_thickness.unwrap_or(0.0);
+        let pos = self.pos + V2::new(offset, offset);
+        let height = f64::from(ctx.text_size(font_id, " ").unwrap().1);
+        let mut text_width = |text: &str| {
+            if text.is_empty() {
+                return 0.0;
+            }
+            f64::from(ctx.text_size(font_id, text).unwrap().0)
+        };
+
+        let selection = state
+            .selection_parts()
+            .filter(|_| state.active)
+            .map(|(before, selected)| (text_width(before), text_width(selected)));
+        let mut caret_x = text_width(state.text_before_caret());
+        let composition = state
+            .composition
+            .as_deref()
+            .filter(|text| state.active && !text.is_empty())
+            .map(|text| (text, text_width(text)));
+
+        if let Some((x, width)) = selection {
+            ctx.draw_rect(
+                (60, 100, 170),
+                pos + V2::new(x, 0.0),
+                V2::new(width, height),
+            )
+            .unwrap();
+        }
+        if !state.text().is_empty() {
+            let text = ctx.render_text(font_id, state.text(), color).unwrap();
+            ctx.draw_texture(text.texture, pos).unwrap();
+        } else if !state.placeholder.is_empty() && composition.is_none() {
+            let text = ctx
+                .render_text(font_id, &state.placeholder, (150, 150, 150))
+                .unwrap();
+            ctx.draw_texture(text.texture, pos).unwrap();
+        }
+        if let Some((composition, width)) = composition {
+            let composition_pos = pos + V2::new(caret_x, 0.0);
+            let text = ctx.render_text(font_id, composition, color).unwrap();
+            ctx.draw_texture(text.texture, composition_pos).unwrap();
+            ctx.draw_rect(
+                color,
+                composition_pos + V2::new(0.0, height - 2.0),
+                V2::new(width, 2.0),
+            )
+            .unwrap();
+            caret_x += width;
+        }
+        if state.active {
+            ctx.draw_rect(color, pos + V2::new(caret_x, 0.0), V2::new(2.0, height))
+                .unwrap();
+        }
+    }
     pub fn draw(&self, ctx: &mut impl UiContext) {
         if !self.inner.visible {
             return;
@@ -131,6 +219,7 @@ impl LayoutTreeLeaf<'_> {
                 ctx.draw_texture(text.texture, self.pos + V2::new(offset, offset))
                     .unwrap();
             }
+            Kind::TextInput { state, font } => self.draw_text_input(ctx, state, font),
             Kind::Image(src) => {
                 let texture = ctx.load_texture(src).unwrap();
                 let texture_size = ctx.texture_size(texture).unwrap();
@@ -293,6 +382,18 @@ impl CanCreateLayoutTree for Node {
                 let leaf = build_leaf(self, node_id, pos_transformer, parent_pos, size);
                 LayoutTree::Single(leaf)
             }
+            Kind::TextInput { state, font } => {
+                let font_size = self.font_size.unwrap_or(15);
+                let font_id = ctx.load_font(font, font_size).unwrap();
+                let text = [state.text(), &state.placeholder]
+                    .into_iter()
+                    .find(|text| !text.is_empty())
+                    .unwrap_or(" ");
+                let size = ctx.text_size(font_id, text).unwrap();
+                let size = V2::new(f64::from(size.0), f64::from(size.1));
+                let leaf = build_leaf(self, node_id, pos_transformer, parent_pos, size);
+                LayoutTree::Single(leaf)
+            }
             Kind::Rect | Kind::Image(_) => {
                 let leaf = build_leaf(
                     self,
@@ -4,6 +4,7 @@ mod ui_context;
 pub mod builder;
 pub mod focus;
 pub mod id_offset;
+pub mod text_input;
 pub mod utils;
 
 use crate::{Context, V2};
@@ -15,6 +16,7 @@ use std::{path::PathBuf, rc::Rc};
 
 use self::{
     layout::{CanCreateLayoutTree, LayoutTree, NoTransform},
+    text_input::TextInputState,
     ui_context::UiContext,
 };
 
@@ -51,7 +53,14 @@ pub enum Kind {
     Vert(Vec<InternalNodeId>),
     Hori(Vec<InternalNodeId>),
     Stack(Vec<InternalNodeId>),
-    Text { text: String, font: PathBuf },
+    Text {
+        text: String,
+        font: PathBuf,
+    },
+    TextInput {
+        state: TextInputState,
+        font: PathBuf,
+    },
     Image(PathBuf),
 }
 
@@ -121,6 +130,8 @@ pub struct Dom {
     root_id: InternalNodeId,
     event_queue: Vec<(EventId, InternalNodeId)>,
     event_handlers: Vec<(EventId, EventHandler)>,
+    text_input_activated: bool,
+    text_input_started: bool,
 }
 
 impl Dom {
@@ -139,6 +150,8 @@ impl Dom {
             id_counter,
             event_queue: Vec::new(),
             event_handlers: Vec::new(),
+            text_input_activated: false,
+            text_input_started: false,
         }
     }
 
@@ -255,7 +268,106 @@ impl Dom {
         }
     }
 
+    pub fn editing_text(&self) -> bool {
+        self.nodes
+            .iter()
+            .any(|(_, node)| matches!(&node.kind, Kind::TextInput { state, .. } if state.active))
+    }
+
+    fn set_active_text_input(&mut self, id: Option<InternalNodeId>) {
+        for (node_id, node) in &mut self.nodes {
+            let Kind::TextInput { state, .. } = &mut node.kind else {
+                continue;
+            };
+            let active = id == Some(*node_id);
+            if active && !state.active {
+                self.text_input_activated = true;
+            }
+            if !active {
+                state.composition = None;
+            }
+            state.active = active;
+        }
+    }
+
+    fn edit_text_input(&mut self, ctx: &mut Context) {
+        use crate::Keycode;
+
+        // the key activating the input, e.g. return through focus, is not an edit
+        let activated = std::mem::take(&mut self.text_input_activated);
+        let Some(state) = self
+            .nodes
+            .iter_mut()
+            .find_map(|(_, node)| match &mut node.kind {
+                Kind::TextInput { state, .. } if state.active => Some(state),
+                _ => None,
+            })
+        else {
+            return;
+        };
+
+        let key = |keycode| ctx.key_pressed_or_repeated(keycode);
+        let ctrl = ctx.key_pressed(Keycode::LCtrl) || ctx.key_pressed(Keycode::RCtrl);
+        let shift = ctx.key_pressed(Keycode::LShift) || ctx.key_pressed(Keycode::RShift);
+
+        state.composition = ctx.text_editing().map(str::to_string);
+        if !ctrl {
+            state.insert(ctx.text_input());
+        }
+        if key(Keycode::Backspace) {
+            state.backspace();
+        }
+        if key(Keycode::Delete) {
+            state.delete();
+        }
+        if key(Keycode::Left) {
+            state.move_left(shift);
+        }
+        if key(Keycode::Right) {
+            state.move_right(shift);
+        }
+        if key(Keycode::Home) {
+            state.move_home(shift);
+        }
+        if key(Keycode::End) {
+            state.move_end(shift);
+        }
+
+        let mut copied = None;
+        if ctrl && key(Keycode::A) {
+            state.select_all();
+        }
+        if ctrl && key(Keycode::C) {
+            copied = state.selected_text().map(str::to_string);
+        }
+        if ctrl && key(Keycode::X) {
+            copied = state.cut();
+        }
+        if ctrl && key(Keycode::V) {
+            if let Some(text) = ctx.clipboard_text() {
+                state.insert(&text.replace(['\n', '\r'], ""));
+            }
+        }
+        if !activated && ctx.key_just_pressed(Keycode::Return) {
+            state.active = false;
+            state.composition = None;
+        }
+
+        if let Some(text) = copied {
+            if let Err(err) = ctx.set_clipboard_text(&text) {
+                println!("ui warning: unable to copy to clipboard: {err}");
+            }
+        }
+    }
+
     fn click_node(&mut self, id: InternalNodeId) {
+        if let Some(Node {
+            kind: Kind::TextInput { .. },
+            ..
+        }) = self.select_node(id)
+        {
+            self.set_active_text_input(Some(id));
+        }
         if let Some((id, node)) = self.nodes.iter().find(|node| id == node.0) {
             if let Some(event_id) = node.on_click {
                 self.event_queue.push((event_id, *id));
@@ -266,13 +378,30 @@ impl Dom {
     pub fn update(&mut self, ctx: &mut Context) {
         let tree = self.build_layout_tree(ctx);
         tree.draw(ctx);
+        let mut clicked_text_input = None;
         if ctx.mouse_button_just_pressed(crate::MouseButton::Left) {
             let mouse_position = ctx.mouse_position();
             let mouse_position = V2::new(f64::from(mouse_position.0), f64::from(mouse_position.1));
+            // clicking anywhere else stops editing
+            clicked_text_input = Some(tree.resolve_text_input(mouse_position));
             if let Some(event) = tree.resolve_click(mouse_position) {
                 self.event_queue.push(event);
             }
         }
+        if let Some(id) = clicked_text_input {
+            self.set_active_text_input(id);
+        }
+        self.edit_text_input(ctx);
+
+        let editing = self.editing_text();
+        if editing != self.text_input_started {
+            if editing {
+                ctx.start_text_input();
+            } else {
+                ctx.stop_text_input();
+            }
+            self.text_input_started = editing;
+        }
         self.handle_events(ctx);
     }
 }
@@ -0,0 +1,195 @@
+use std::ops::Range;
+
+/// Content and editing state of a `TextInput` node.
+/// Caret and selection anchor are char indices, not byte indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextInputState {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+    pub placeholder: String,
+    pub active: bool,
+    /// Text being composed by the IME, drawn at the caret.
+    pub composition: Option<String>,
+}
+
+impl TextInputState {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        let text = text.into();
+        let caret = text.chars().count();
+        Self {
+            text,
+            caret,
+            anchor: None,
+            placeholder: String::new(),
+            active: false,
+            composition: None,
+        }
+    }
+
+    pub fn with_placeholder<S: Into<String>>(self, placeholder: S) -> Self {
+        Self {
+            placeholder: placeholder.into(),
+            ..self
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        self.text = text.into();
+        self.caret = self.len();
+        self.anchor = None;
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len())
+    }
+
+    /// Text up to the caret, used to find where to draw it.
+    pub fn text_before_caret(&self) -> &str {
+        &self.text[..self.byte_index(self.caret)]
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor?;
+        match anchor.cmp(&self.caret) {
+            std::cmp::Ordering::Less => Some(anchor..self.caret),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(self.caret..anchor),
+        }
+    }
+
+    /// Text before the selection and the selected text, used for drawing it.
+    pub fn selection_parts(&self) -> Option<(&str, &str)> {
+        let selection = self.selection()?;
+        let start = self.byte_index(selection.start);
+        let end = self.byte_index(selection.end);
+        Some((&self.text[..start], &self.text[start..end]))
+    }
+
+    pub fn selected_text(&self) -> Option<&str> {
+        self.selection_parts().map(|(_, selected)| selected)
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        let Some(selection) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        let start = self.byte_index(selection.start);
+        let end = self.byte_index(selection.end);
+        self.text.replace_range(start..end, "");
+        self.caret = selection.start;
+        self.anchor = None;
+        true
+    }
+
+    /// Replaces the selection, if any.
+    pub fn insert(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.delete_selection();
+        let index = self.byte_index(self.caret);
+        self.text.insert_str(index, text);
+        self.caret += text.chars().count();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.delete_selection() || self.caret == 0 {
+            return;
+        }
+        let index = self.byte_index(self.caret - 1);
+        self.text.remove(index);
+        self.caret -= 1;
+    }
+
+    pub fn delete(&mut self) {
+        if self.delete_selection() || self.caret == self.len() {
+            return;
+        }
+        let index = self.byte_index(self.caret);
+        self.text.remove(index);
+    }
+
+    /// Returns the removed text, so it can be put on the clipboard.
+    pub fn cut(&mut self) -> Option<String> {
+        let selected = self.selected_text()?.to_string();
+        self.delete_selection();
+        Some(selected)
+    }
+
+    fn move_caret(&mut self, caret: usize, select: bool) {
+        if select {
+            self.anchor = self.anchor.or(Some(self.caret));
+        } else {
+            self.anchor = None;
+        }
+        self.caret = caret;
+    }
+
+    pub fn move_left(&mut self, select: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.move_caret(selection.start, false),
+            _ => self.move_caret(self.caret.saturating_sub(1), select),
+        }
+    }
+
+    pub fn move_right(&mut self, select: bool) {
+        match self.selection() {
+            Some(selection) if !select => self.move_caret(selection.end, false),
+            _ => self.move_caret((self.caret + 1).min(self.len()), select),
+        }
+    }
+
+    pub fn move_home(&mut self, select: bool) {
+        self.move_caret(0, select);
+    }
+
+    pub fn move_end(&mut self, select: bool) {
+        self.move_caret(self.len(), select);
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.len();
+    }
+}
+
+#[test]
+fn test_text_input_editing() {
+    let mut state = TextInputState::new("hællo");
+    assert_eq!(state.caret(), 5);
+
+    state.backspace();
+    state.move_left(false);
+    state.move_left(true);
+    state.move_left(true);
+    assert_eq!(state.selected_text(), Some("æl"));
+
+    state.insert("e");
+    assert_eq!(state.text(), "hel");
+    assert_eq!(state.text_before_caret(), "he");
+
+    state.move_home(false);
+    state.delete();
+    state.select_all();
+    assert_eq!(state.cut().as_deref(), Some("el"));
+    assert_eq!(state.text(), "");
+    assert_eq!(state.caret(), 0);
+}
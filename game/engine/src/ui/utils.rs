@@ -10,6 +10,30 @@ pub fn change_text_node_content<S: Into<String>>(node: Option<&mut super::Node>,
     *text = new_text.into();
 }
 
+pub fn text_input_content(node: Option<&super::Node>) -> Option<&str> {
+    let Some(super::Node {
+        kind: super::Kind::TextInput { state, .. },
+        ..
+    }) = node
+    else {
+        println!("ui warning: tried to get text input content of non-text-input node");
+        return None;
+    };
+    Some(state.text())
+}
+
+pub fn change_text_input_content<S: Into<String>>(node: Option<&mut super::Node>, new_text: S) {
+    let Some(super::Node {
+        kind: super::Kind::TextInput { state, .. },
+        ..
+    }) = node
+    else {
+        println!("ui warning: tried to change text input content of non-text-input node");
+        return;
+    };
+    state.set_text(new_text);
+}
+
 pub fn change_image_node_content<P: Into<std::path::PathBuf>>(
     node: Option<&mut super::Node>,
     new_path: P,
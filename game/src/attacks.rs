@@ -1,123 +1,121 @@
-use std::f64::consts;
-
 use crate::{
-    hurtbox::{HurtDirection, HurtboxProfile, Outcome},
+    hurtbox::{HurtboxProfile, Outcome},
+    moves::Move,
     player::Player,
 };
-use engine::{max, rigid_body::RigidBody, V2};
+use engine::{max, min, rigid_body::RigidBody, V2};
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum AttackKind {
     Up,
     Down,
     Left,
     Right,
+    HeavyUp,
+    HeavyDown,
+    HeavyLeft,
+    HeavyRight,
+    NeutralAir,
+    ForwardAir,
+    BackAir,
+    UpAir,
+    DownAir,
+    /// Unique to each hero.
+    Special,
+    /// Hits a grabbed opponent without letting go.
+    Pummel,
+    ThrowForward,
+    ThrowBack,
+    ThrowUp,
+    ThrowDown,
 }
 
-pub struct SideAttackProfile {
-    pub direction: HurtDirection,
-}
-
-impl HurtboxProfile for SideAttackProfile {
-    fn outcome(
-        &self,
-        victim: &Player,
-        attacker: Option<&Player>,
-        hurtbox_body: &RigidBody,
-        victim_body: &RigidBody,
-    ) -> Outcome {
-        let attacker = attacker.expect("attack always perpetraited");
+impl AttackKind {
+    pub const ALL: [AttackKind; 19] = [
+        AttackKind::Up,
+        AttackKind::Down,
+        AttackKind::Left,
+        AttackKind::Right,
+        AttackKind::HeavyUp,
+        AttackKind::HeavyDown,
+        AttackKind::HeavyLeft,
+        AttackKind::HeavyRight,
+        AttackKind::NeutralAir,
+        AttackKind::ForwardAir,
+        AttackKind::BackAir,
+        AttackKind::UpAir,
+        AttackKind::DownAir,
+        AttackKind::Special,
+        AttackKind::Pummel,
+        AttackKind::ThrowForward,
+        AttackKind::ThrowBack,
+        AttackKind::ThrowUp,
+        AttackKind::ThrowDown,
+    ];
+
+    pub fn heavy(&self) -> Self {
+        match self {
+            AttackKind::Up | AttackKind::HeavyUp => AttackKind::HeavyUp,
+            AttackKind::Down | AttackKind::HeavyDown => AttackKind::HeavyDown,
+            AttackKind::Left | AttackKind::HeavyLeft => AttackKind::HeavyLeft,
+            AttackKind::Right | AttackKind::HeavyRight => AttackKind::HeavyRight,
+            other => *other,
+        }
+    }
 
-        let power = 200.0;
-        let knockback_per_strength = 5.0;
-        let knockback_per_defence = -5.0;
-        let knockback_per_damage_taken_squared = 0.015;
-        let base_damage_taken_factor = 1.0;
+    pub fn is_aerial(&self) -> bool {
+        matches!(
+            self,
+            AttackKind::NeutralAir
+                | AttackKind::ForwardAir
+                | AttackKind::BackAir
+                | AttackKind::UpAir
+                | AttackKind::DownAir
+        )
+    }
 
-        let hurtbox_vel = hurtbox_body.vel.len();
+    pub fn is_throw(&self) -> bool {
+        matches!(
+            self,
+            AttackKind::ThrowForward
+                | AttackKind::ThrowBack
+                | AttackKind::ThrowUp
+                | AttackKind::ThrowDown
+        )
+    }
 
-        let velocity = hurtbox_vel
-            + victim.damage_taken
-                * (base_damage_taken_factor
-                    + stat_factor(attacker.hero.strength_points) * knockback_per_strength
-                    + stat_factor(victim.hero.defence_points) * knockback_per_defence)
-            + victim.damage_taken.powi(2) * knockback_per_damage_taken_squared
-            + power;
-
-        let victim_body_left = V2::new(
-            victim_body.pos.x,
-            victim_body.pos.y + victim_body.size.y / 2.0,
-        );
-        let victim_body_right = V2::new(
-            victim_body.pos.x + victim_body.size.x,
-            victim_body.pos.y + victim_body.size.y / 2.0,
-        );
-        let hurtbox_body_center_left = V2::new(
-            hurtbox_body.pos.x,
-            hurtbox_body.pos.y + hurtbox_body.size.y / 2.0,
-        );
-        let hurtbox_body_center_right = V2::new(
-            hurtbox_body.pos.x + hurtbox_body.size.x,
-            hurtbox_body.pos.y + hurtbox_body.size.y / 2.0,
-        );
-
-        let delta_vel = match self.direction {
-            HurtDirection::Left => {
-                let extended_hurtbox_body_left =
-                    V2::new(victim_body_left.x, hurtbox_body_center_left.y);
-
-                let attack_angle = attack_angle(
-                    extended_hurtbox_body_left - hurtbox_body_center_right,
-                    victim_body_left - hurtbox_body_center_right,
-                );
-
-                let max_angle = consts::PI / 2.0;
-
-                let attack_angle = max(attack_angle, max_angle);
-
-                let attack_angle_percentage = attack_angle / max_angle;
-                let y_vel = attack_angle_percentage * velocity;
-
-                if hurtbox_body.pos.y < victim_body.pos.y {
-                    V2::new(-velocity, y_vel)
-                } else {
-                    V2::new(-velocity, -y_vel)
-                }
-            }
-            HurtDirection::Right => {
-                let extended_hurtbox_body_right =
-                    V2::new(victim_body_right.x, hurtbox_body_center_right.y);
-
-                let attack_angle = attack_angle(
-                    extended_hurtbox_body_right - hurtbox_body_center_left,
-                    victim_body_right - hurtbox_body_center_left,
-                );
-
-                let max_angle = consts::PI / 2.0;
-
-                let attack_angle = max(attack_angle, max_angle);
-
-                let attack_angle_percentage = attack_angle / max_angle;
-                let y_vel = attack_angle_percentage * velocity;
-
-                if hurtbox_body.pos.y < victim_body.pos.y {
-                    V2::new(velocity, y_vel)
-                } else {
-                    V2::new(velocity, -y_vel)
-                }
-            }
-            _ => unreachable!(),
-        };
+    /// Moves written facing right, mirrored when the attacker faces left.
+    pub fn uses_facing(&self) -> bool {
+        self.is_aerial() || self.is_throw() || *self == AttackKind::Special
+    }
 
-        Outcome {
-            damage: 10.0,
-            delta_vel,
-            stun_time: Some(stun_time_by_velocity(0.3, delta_vel)),
+    /// Name of the move file in `assets/moves`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttackKind::Up => "up",
+            AttackKind::Down => "down",
+            AttackKind::Left => "left",
+            AttackKind::Right => "right",
+            AttackKind::HeavyUp => "heavy_up",
+            AttackKind::HeavyDown => "heavy_down",
+            AttackKind::HeavyLeft => "heavy_left",
+            AttackKind::HeavyRight => "heavy_right",
+            AttackKind::NeutralAir => "neutral_air",
+            AttackKind::ForwardAir => "forward_air",
+            AttackKind::BackAir => "back_air",
+            AttackKind::UpAir => "up_air",
+            AttackKind::DownAir => "down_air",
+            AttackKind::Special => "special",
+            AttackKind::Pummel => "pummel",
+            AttackKind::ThrowForward => "throw_forward",
+            AttackKind::ThrowBack => "throw_back",
+            AttackKind::ThrowUp => "throw_up",
+            AttackKind::ThrowDown => "throw_down",
         }
     }
 }
 
-pub struct UpAttackProfile;
-impl HurtboxProfile for UpAttackProfile {
+impl HurtboxProfile for Move {
     fn outcome(
         &self,
         victim: &Player,
@@ -125,102 +123,59 @@ impl HurtboxProfile for UpAttackProfile {
         hurtbox_body: &RigidBody,
         victim_body: &RigidBody,
     ) -> Outcome {
-        let attacker = attacker.expect("attack always perpetraited");
-
-        let power = 600.0;
-        let knockback_per_strength = 5.0;
-        let knockback_per_defence = -5.0;
-        let knockback_per_damage_taken_squared = 0.015;
-        let base_damage_taken_factor = 1.0;
+        let knockback = &self.knockback;
+        let attacker_strength = attacker.map(|a| a.hero.strength_points).unwrap_or(0);
 
         let hurtbox_vel = hurtbox_body.vel.len();
 
         let velocity = hurtbox_vel
             + victim.damage_taken
-                * (base_damage_taken_factor
-                    + stat_factor(attacker.hero.strength_points) * knockback_per_strength
-                    + stat_factor(victim.hero.defence_points) * knockback_per_defence)
-            + victim.damage_taken.powi(2) * knockback_per_damage_taken_squared
-            + power;
-
-        let victim_body_top = V2::new(
-            victim_body.pos.x + victim_body.size.x / 2.0,
-            victim_body.pos.y,
-        );
-        let hurtbox_body_center_bottom = V2::new(
-            hurtbox_body.pos.x + hurtbox_body.size.x / 2.0,
-            hurtbox_body.pos.y + hurtbox_body.size.y,
-        );
-        let hurtbox_body_center_top = V2::new(
-            hurtbox_body.pos.x + hurtbox_body.size.x / 2.0,
-            hurtbox_body.pos.y,
-        );
-
-        let extended_hurtbox_body_top = V2::new(hurtbox_body_center_top.x, victim_body_top.y);
-
-        let attack_angle = attack_angle(
-            extended_hurtbox_body_top - hurtbox_body_center_bottom,
-            victim_body_top - hurtbox_body_center_bottom,
-        );
-
-        let max_angle = consts::PI / 2.0;
-
-        let attack_angle = max(attack_angle, max_angle);
-
-        let attack_angle_percentage = attack_angle / max_angle;
-        let angle_factor = 0.5;
-        let x_vel = attack_angle_percentage * velocity * angle_factor;
-
-        let delta_vel = if hurtbox_body.pos.x < victim_body.pos.x {
-            V2::new(x_vel, -velocity)
+                * (knockback.per_damage_taken
+                    + stat_factor(attacker_strength) * knockback.per_strength
+                    + stat_factor(victim.hero.defence_points) * knockback.per_defence)
+            + victim.damage_taken.powi(2) * knockback.per_damage_taken_squared
+            + knockback.power;
+
+        let mut direction = knockback.direction();
+        // projectiles knock back the way they fly, e.g. after a reflection
+        if self.projectile.is_some() && hurtbox_body.vel.x * direction.x < 0.0 {
+            direction.x = -direction.x;
+        }
+        let across = V2::new(-direction.y, direction.x);
+        let across = if (victim_body.pos - hurtbox_body.pos).dot(across) > 0.0 {
+            across
         } else {
-            V2::new(-x_vel, -velocity)
+            across.reverse()
+        };
+        let delta_vel = (direction + across.extend(knockback.aim)).extend(velocity);
+
+        // below tumbling speed the stun stays short, so light hits can't
+        // lock the victim down
+        let tumble_speed = 600.0;
+        let max_non_tumble_stun = 0.4;
+        let tumble = delta_vel.len() >= tumble_speed;
+        let stun = max(self.stun, delta_vel.len() * self.stun_per_velocity);
+        let stun = if tumble {
+            stun
+        } else {
+            min(stun, max(self.stun, max_non_tumble_stun))
         };
 
-        Outcome {
-            damage: 10.0,
-            delta_vel,
-            stun_time: Some(stun_time_by_velocity(0.3, delta_vel)),
-        }
-    }
-}
-
-pub struct DownAttackProfile;
-impl HurtboxProfile for DownAttackProfile {
-    fn outcome(
-        &self,
-        victim: &Player,
-        _attacker: Option<&Player>,
-        hurtbox_body: &RigidBody,
-        _victim_body: &RigidBody,
-    ) -> Outcome {
-        let power = 600.0;
-        let knockback_per_damage_taken_squared = 0.0025;
-
-        let hurtbox_vel = hurtbox_body.vel.len();
+        let base_hitlag = 0.03;
+        let hitlag_per_damage = 0.006;
+        let max_hitlag = 0.25;
 
-        let delta_vel = V2::new(
-            0.0,
-            -(hurtbox_vel
-                + victim.damage_taken.powi(2) * knockback_per_damage_taken_squared
-                + power),
-        );
         Outcome {
-            damage: 5.0,
+            damage: self.damage,
             delta_vel,
-            stun_time: Some(0.5),
+            stun_time: Some(stun),
+            hitlag: min(base_hitlag + self.damage * hitlag_per_damage, max_hitlag),
+            attacker_hitlag: self.projectile.is_none(),
+            tumble,
         }
     }
 }
 
-fn attack_angle(lhs: V2, rhs: V2) -> f64 {
-    (lhs.len() / rhs.len()).acos()
-}
-
-fn stun_time_by_velocity(stun_time: f64, delta_vel: V2) -> f64 {
-    max(stun_time, delta_vel.len() / 2500.0)
-}
-
 fn strength_and_defence_modifier(victim_defence: i64, owner_strength: i64) -> f64 {
     let base_strength = 1.0;
     let base_defence = 1.0;
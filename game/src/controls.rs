@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashMap};
+
+use engine::{Component, Context, ControllerAxis, ControllerButton, Keycode, MouseButton};
+use serde::{Deserialize, Serialize};
+
+use crate::{gamepad::Gamepad, keyset::Keyset, player::PlayerKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    LightAttack,
+    HeavyAttack,
+    Special,
+    Shield,
+    Grab,
+    Dodge,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::LightAttack,
+        Action::HeavyAttack,
+        Action::Special,
+        Action::Shield,
+        Action::Grab,
+        Action::Dodge,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Up => "Jump",
+            Action::Down => "Down",
+            Action::LightAttack => "Attack",
+            Action::HeavyAttack => "Heavy",
+            Action::Special => "Special",
+            Action::Shield => "Shield",
+            Action::Grab => "Grab",
+            Action::Dodge => "Dodge",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Controller bindings always refer to the gamepad assigned to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key(Keycode),
+    Mouse(MouseButton),
+    Button(ControllerButton),
+    Axis(ControllerAxis, AxisDirection),
+}
+
+impl Binding {
+    pub fn is_controller(&self) -> bool {
+        match self {
+            Binding::Key(_) | Binding::Mouse(_) => false,
+            Binding::Button(_) | Binding::Axis(_, _) => true,
+        }
+    }
+
+    /// Between 0 and 1, only axes are ever in between.
+    pub fn value(&self, ctx: &Context, gamepad: Option<&Gamepad>) -> f64 {
+        let pressed = match self {
+            Binding::Key(key) => ctx.key_pressed(*key),
+            Binding::Mouse(button) => ctx.mouse_button_pressed(*button),
+            Binding::Button(button) => {
+                gamepad.is_some_and(|gamepad| ctx.controller_button_pressed(gamepad.0, *button))
+            }
+            Binding::Axis(axis, direction) => {
+                let Some(gamepad) = gamepad else {
+                    return 0.0;
+                };
+                let deadzone = 0.2;
+                let value = ctx.controller_axis(gamepad.0, *axis);
+                let value = match direction {
+                    AxisDirection::Positive => value,
+                    AxisDirection::Negative => -value,
+                };
+                if value < deadzone {
+                    return 0.0;
+                }
+                // rescale so the value starts at 0 right outside the deadzone
+                return (value.min(1.0) - deadzone) / (1.0 - deadzone);
+            }
+        };
+        if pressed {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => key.name(),
+            Binding::Mouse(button) => format!("Mouse {}", mouse_button_name(*button)),
+            Binding::Button(button) => format!("Pad {}", button.string()),
+            Binding::Axis(axis, direction) => {
+                format!("Pad {}{}", axis.string(), axis_direction_sign(*direction))
+            }
+        }
+    }
+}
+
+fn mouse_button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Middle => "middle",
+        MouseButton::Right => "right",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+        MouseButton::Unknown => "unknown",
+    }
+}
+
+fn axis_direction_sign(direction: AxisDirection) -> &'static str {
+    match direction {
+        AxisDirection::Positive => "+",
+        AxisDirection::Negative => "-",
+    }
+}
+
+impl From<Binding> for String {
+    fn from(value: Binding) -> Self {
+        match value {
+            Binding::Key(key) => format!("key:{}", key.name()),
+            Binding::Mouse(button) => format!("mouse:{}", mouse_button_name(button)),
+            Binding::Button(button) => format!("button:{}", button.string()),
+            Binding::Axis(axis, direction) => {
+                format!("axis:{}{}", axis.string(), axis_direction_sign(direction))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid binding '{value}'");
+        let (kind, name) = value.split_once(':').ok_or_else(invalid)?;
+        let binding = match kind {
+            "key" => Keycode::from_name(name).map(Binding::Key),
+            "mouse" => match name {
+                "left" => Some(MouseButton::Left),
+                "middle" => Some(MouseButton::Middle),
+                "right" => Some(MouseButton::Right),
+                "x1" => Some(MouseButton::X1),
+                "x2" => Some(MouseButton::X2),
+                _ => None,
+            }
+            .map(Binding::Mouse),
+            "button" => ControllerButton::from_string(name).map(Binding::Button),
+            "axis" => {
+                let direction = match name.chars().last() {
+                    Some('+') => Some(AxisDirection::Positive),
+                    Some('-') => Some(AxisDirection::Negative),
+                    _ => None,
+                };
+                direction.and_then(|direction| {
+                    ControllerAxis::from_string(&name[..name.len() - 1])
+                        .map(|axis| Binding::Axis(axis, direction))
+                })
+            }
+            _ => None,
+        };
+        binding.ok_or_else(invalid)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Bindings(BTreeMap<Action, Vec<Binding>>);
+
+impl Bindings {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    pub fn bind(mut self, action: Action, binding: Binding) -> Self {
+        self.0.entry(action).or_default().push(binding);
+        self
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Adds the defaults of actions without bindings, e.g. actions added
+    /// after the bindings were saved.
+    pub fn fill_missing(&mut self, defaults: &Bindings) {
+        for (action, bindings) in &defaults.0 {
+            self.0.entry(*action).or_insert_with(|| bindings.clone());
+        }
+    }
+
+    /// Replaces the bindings of the action on the same kind of device,
+    /// so rebinding a key keeps the controller bindings and vice versa.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|existing| existing.is_controller() != binding.is_controller());
+        bindings.push(binding);
+    }
+
+    pub fn value(&self, ctx: &Context, gamepad: Option<&Gamepad>, action: Action) -> f64 {
+        self.get(action)
+            .iter()
+            .map(|binding| binding.value(ctx, gamepad))
+            .fold(0.0, f64::max)
+    }
+
+    pub fn label(&self, action: Action, controller: bool) -> String {
+        let labels = self
+            .get(action)
+            .iter()
+            .filter(|binding| binding.is_controller() == controller)
+            .map(Binding::label)
+            .collect::<Vec<_>>();
+        if labels.is_empty() {
+            "unbound".to_string()
+        } else {
+            labels.join(", ")
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+pub struct Controls {
+    pub left: Bindings,
+    pub right: Bindings,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            left: Keyset::Wasd.bindings(),
+            right: Keyset::ArrowKeys.bindings(),
+        }
+    }
+}
+
+impl Controls {
+    const PATH: &'static str = "controls.json";
+
+    pub fn load() -> Self {
+        let Ok(json) = std::fs::read_to_string(Self::PATH) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&json) {
+            Ok(mut controls) => {
+                let defaults = Self::default();
+                controls.left.fill_missing(&defaults.left);
+                controls.right.fill_missing(&defaults.right);
+                controls
+            }
+            Err(err) => {
+                println!(
+                    "unable to read {}, using default controls: {err}",
+                    Self::PATH
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        if let Err(err) = std::fs::write(Self::PATH, json) {
+            println!("unable to save {}: {err}", Self::PATH);
+        }
+    }
+
+    pub fn bindings(&self, kind: &PlayerKind) -> &Bindings {
+        match kind {
+            PlayerKind::Left => &self.left,
+            PlayerKind::Right => &self.right,
+        }
+    }
+
+    pub fn bindings_mut(&mut self, kind: &PlayerKind) -> &mut Bindings {
+        match kind {
+            PlayerKind::Left => &mut self.left,
+            PlayerKind::Right => &mut self.right,
+        }
+    }
+}
+
+/// Action values of the current and previous frame, so `just_pressed`
+/// works the same for keys, buttons and axes.
+#[derive(Clone, Debug, Default)]
+pub struct ActionState {
+    current: HashMap<Action, f64>,
+    previous: HashMap<Action, f64>,
+}
+
+impl ActionState {
+    pub fn update(&mut self, ctx: &Context, bindings: &Bindings, gamepad: Option<&Gamepad>) {
+        self.previous = std::mem::take(&mut self.current);
+        for action in Action::ALL {
+            self.current
+                .insert(action, bindings.value(ctx, gamepad, action));
+        }
+    }
+
+    pub fn value(&self, action: Action) -> f64 {
+        self.current.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.value(action) > 0.5
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed(action) && self.previous.get(&action).copied().unwrap_or(0.0) <= 0.5
+    }
+
+    /// Between -1 and 1, holding both directions cancels out.
+    pub fn horizontal(&self) -> f64 {
+        self.value(Action::Right) - self.value(Action::Left)
+    }
+
+    /// Between -1 and 1, down is positive like screen coordinates.
+    pub fn vertical(&self) -> f64 {
+        self.value(Action::Down) - self.value(Action::Up)
+    }
+}
@@ -0,0 +1,286 @@
+use engine::ui::{self, utils::change_text_node_content};
+use engine::{query, spawn, Component, Context, SharedPtr, System};
+
+use crate::{
+    controls::{Action, AxisDirection, Binding, Controls},
+    main_menu::MainMenuSystem,
+    player::PlayerKind,
+    ui_components::Button,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+struct BindingSlot {
+    player: usize,
+    action: usize,
+    controller: bool,
+}
+
+impl BindingSlot {
+    const PLAYERS: [PlayerKind; 2] = [PlayerKind::Left, PlayerKind::Right];
+
+    fn all() -> impl Iterator<Item = BindingSlot> {
+        (0..Self::PLAYERS.len()).flat_map(|player| {
+            (0..Action::ALL.len()).flat_map(move |action| {
+                [false, true].map(|controller| BindingSlot {
+                    player,
+                    action,
+                    controller,
+                })
+            })
+        })
+    }
+
+    fn kind(&self) -> &PlayerKind {
+        &Self::PLAYERS[self.player]
+    }
+
+    fn action(&self) -> Action {
+        Action::ALL[self.action]
+    }
+
+    /// Offset past `Node` and `Event`, so slot ids never collide with them.
+    fn id(&self) -> u64 {
+        let index = (self.player * Action::ALL.len() + self.action) * 2 + self.controller as usize;
+        100 + index as u64
+    }
+
+    fn node_id(&self) -> ui::NodeId {
+        ui::NodeId::from_u64(self.id())
+    }
+
+    fn event_id(&self) -> ui::EventId {
+        ui::EventId::from_u64(self.id())
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct ControlsMenu {
+    system_id: u64,
+    dom: SharedPtr<ui::Dom>,
+    focus: SharedPtr<ui::focus::Focus>,
+    controls: SharedPtr<Controls>,
+    listening: SharedPtr<Option<BindingSlot>>,
+}
+
+#[repr(u64)]
+enum Node {
+    Hint,
+    Reset,
+    Back,
+}
+
+#[repr(u64)]
+enum Event {
+    Reset,
+    Back,
+}
+
+impl From<Node> for ui::NodeId {
+    fn from(value: Node) -> Self {
+        Self::from_u64(value as u64)
+    }
+}
+
+impl From<Event> for ui::EventId {
+    fn from(value: Event) -> Self {
+        Self::from_u64(value as u64)
+    }
+}
+
+pub struct ControlsMenuSystem(pub u64);
+impl System for ControlsMenuSystem {
+    fn on_add(&self, ctx: &mut engine::Context) -> Result<(), engine::Error> {
+        use ui::constructors::{Hori, Rect, Stack, Text, Vert};
+
+        let system_id = self.0;
+
+        let player_column = |player: usize, title: &str| {
+            let rows = (0..Action::ALL.len()).map(|action| {
+                let slot = |controller| BindingSlot {
+                    player,
+                    action,
+                    controller,
+                };
+                Hori([
+                    Text(Action::ALL[action].name()).width(80),
+                    Button("")
+                        .width(160)
+                        .padding(5)
+                        .id(slot(false).node_id())
+                        .on_click(slot(false).event_id()),
+                    Button("")
+                        .width(160)
+                        .padding(5)
+                        .id(slot(true).node_id())
+                        .on_click(slot(true).event_id()),
+                ])
+                .gap(8)
+            });
+            let header = Hori([
+                Rect().width(80),
+                Text("Keyboard").width(174),
+                Text("Gamepad").width(174),
+            ])
+            .gap(8);
+            Vert([Text(title).font_size(32), header].into_iter().chain(rows)).gap(8)
+        };
+
+        let mut dom = ui::Dom::new(
+            Stack([Vert([
+                Text("Controls").font_size(48),
+                Hori([
+                    player_column(0, "Player 1"),
+                    Rect().width(40),
+                    player_column(1, "Player 2"),
+                ]),
+                Rect().height(20),
+                Text("Select a binding, then press the key or button to use").id(Node::Hint),
+                Hori([
+                    Button("Reset")
+                        .background_color((50, 50, 50))
+                        .id(Node::Reset)
+                        .on_click(Event::Reset),
+                    Button("Back")
+                        .background_color((50, 50, 50))
+                        .id(Node::Back)
+                        .on_click(Event::Back),
+                ])
+                .gap(8),
+            ])
+            .gap(8)])
+            .background_color((50, 50, 50))
+            .font_size(20)
+            .width(1280)
+            .height(720),
+        );
+
+        let controls = SharedPtr::new(Controls::load());
+        let listening = SharedPtr::new(None);
+
+        for slot in BindingSlot::all() {
+            let listening = listening.clone();
+            dom.add_event_handler(slot.event_id(), move |dom, _ctx, _node_id| {
+                *listening.lock() = Some(slot);
+                change_text_node_content(dom.select_mut(slot.node_id()), "...");
+            });
+        }
+
+        {
+            let controls = controls.clone();
+            let listening = listening.clone();
+            dom.add_event_handler(Event::Reset, move |dom, _ctx, _node_id| {
+                *listening.lock() = None;
+                *controls.lock() = Controls::default();
+                controls.lock().save();
+                update_binding_labels(dom, &controls.lock());
+            });
+        }
+
+        dom.add_event_handler(Event::Back, move |_dom, ctx, _node_id| {
+            ctx.remove_system(system_id);
+            ctx.add_system(MainMenuSystem);
+        });
+
+        update_binding_labels(&mut dom, &controls.lock());
+
+        let focus_nodes = BindingSlot::all()
+            .map(|slot| slot.node_id())
+            .chain([Node::Reset.into(), Node::Back.into()]);
+
+        spawn!(
+            ctx,
+            ControlsMenu {
+                system_id: self.0,
+                dom: SharedPtr::new(dom),
+                focus: SharedPtr::new(ui::focus::Focus::new(focus_nodes)),
+                controls,
+                listening,
+            }
+        );
+
+        Ok(())
+    }
+
+    fn on_update(&self, ctx: &mut engine::Context, _delta: f64) -> Result<(), engine::Error> {
+        for id in query!(ctx, ControlsMenu) {
+            let menu = ctx.select::<ControlsMenu>(id).clone();
+            let mut dom = menu.dom.lock();
+            let listening = *menu.listening.lock();
+            match listening {
+                Some(slot) => {
+                    // captured before the dom handles clicks, so the click
+                    // selecting a binding is never bound itself
+                    if let Some(binding) = captured_binding(ctx, slot.controller) {
+                        *menu.listening.lock() = None;
+                        let mut controls = menu.controls.lock();
+                        controls
+                            .bindings_mut(slot.kind())
+                            .rebind(slot.action(), binding);
+                        controls.save();
+                        update_binding_labels(&mut dom, &controls);
+                    }
+                }
+                None => {
+                    menu.focus.lock().update(&mut dom, ctx);
+                }
+            }
+            dom.update(ctx);
+        }
+        Ok(())
+    }
+
+    fn on_remove(&self, ctx: &mut engine::Context) -> Result<(), engine::Error> {
+        for id in query!(ctx, ControlsMenu) {
+            let menu = ctx.select::<ControlsMenu>(id).clone();
+            if menu.system_id == self.0 {
+                ctx.despawn(id);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn update_binding_labels(dom: &mut ui::Dom, controls: &Controls) {
+    for slot in BindingSlot::all() {
+        let label = controls
+            .bindings(slot.kind())
+            .label(slot.action(), slot.controller);
+        change_text_node_content(dom.select_mut(slot.node_id()), label);
+    }
+}
+
+fn captured_binding(ctx: &Context, controller: bool) -> Option<Binding> {
+    if !controller {
+        let key = ctx.keys_just_pressed().into_iter().next().map(Binding::Key);
+        let mouse_button = || {
+            ctx.mouse_buttons_just_pressed()
+                .into_iter()
+                .next()
+                .map(Binding::Mouse)
+        };
+        return key.or_else(mouse_button);
+    }
+    let button = ctx
+        .controller_buttons_just_pressed()
+        .into_iter()
+        .next()
+        .map(|(_, button)| Binding::Button(button));
+    let axis = || {
+        use engine::ControllerAxis::*;
+        ctx.active_controllers().find_map(|id| {
+            [LeftX, LeftY, RightX, RightY, TriggerLeft, TriggerRight]
+                .into_iter()
+                .find_map(|axis| {
+                    let value = ctx.controller_axis(id, axis);
+                    if value > 0.7 {
+                        Some(Binding::Axis(axis, AxisDirection::Positive))
+                    } else if value < -0.7 {
+                        Some(Binding::Axis(axis, AxisDirection::Negative))
+                    } else {
+                        None
+                    }
+                })
+        })
+    };
+    button.or_else(axis)
+}
@@ -1,19 +1,27 @@
 use engine::{
-    collision::{resolve_position_default, CollisionResolver, DefaultResolver, ShallowCollider},
+    collision::{
+        resolve_position_default, CollisionResolver, DefaultResolver, PolygonCollider,
+        ShallowCollider, Trigger, TriggerSystem, DEFAULT_LAYER,
+    },
+    kinematic::KinematicSystem,
     physics::QuadDirection,
     query, query_one,
-    rigid_body::{DragSystem, GravitySystem, RigidBody, VelocitySystem},
+    rigid_body::{DragSystem, GravitySystem, PhysicsConfig, RigidBody, VelocitySystem},
     spawn, CollisionSystem, Component, IdAccumulator, SharedPtr, SolidCollider, System, V2,
 };
 
 use crate::{
+    controls::{Bindings, Controls},
     hud::{player_damage_color, HudSystem},
-    hurtbox::{Hitbox, Hurtbox, HurtboxSystem, Victim},
-    keyset::Keyset,
-    knockoff::{DeathAnimationSystem, KnockoffSystem},
+    hurtbox::{Hurtbox, HurtboxSystem, Victim, HITBOX_LAYER, HURTBOX_LAYER},
+    knockoff::{BlastZone, DeathAnimationSystem, KnockoffSystem},
+    ledge::spawn_ledges,
+    match_rules::{MatchRules, MatchState},
+    moves::MoveSet,
     player::{Player, PlayerKind},
     player_interaction::{PlayerInteraction, PlayerInteractionSystem},
     server::Server,
+    shield::Shield,
     sound_player::SoundPlayer,
     sprite_renderer::{Sprite, SpriteRenderer},
     timer::Timer,
@@ -53,11 +61,13 @@ pub struct HeroesOnBoard {
 impl System for GameSystem {
     fn on_add(&self, ctx: &mut engine::Context) -> Result<(), engine::Error> {
         let mut systems = IdAccumulator::new();
+        systems += ctx.add_system(KinematicSystem);
         systems += ctx.add_system(CollisionSystem);
         systems += ctx.add_system(VelocitySystem);
         systems += ctx.add_system(SpriteRenderer);
         systems += ctx.add_system(GravitySystem);
         systems += ctx.add_system(DragSystem);
+        systems += ctx.add_system(TriggerSystem);
         systems += ctx.add_system(HurtboxSystem);
         systems += ctx.add_system(KnockoffSystem);
         systems += ctx.add_system(PlayerInteractionSystem);
@@ -69,35 +79,55 @@ impl System for GameSystem {
 
         notify_server_about_player_colors(ctx);
 
+        let controls = Controls::load();
+        let rules = ctx.clone_one::<MatchRules>();
+
         let mut children = IdAccumulator::new();
+        children += spawn!(ctx, PhysicsConfig::new());
+        // loaded on the start game screen, which reports it failing
+        children += query_one!(ctx, MoveSet);
+        children += spawn!(ctx, MatchState::new(&rules));
         children += spawn!(
             ctx,
             Sprite::new(background).layer(2),
             RigidBody::new().with_size(V2::new(1280.0, 720.0)),
         );
-        children += self.spawn_player(ctx, V2::new(400.0, 350.0), Keyset::Wasd, PlayerKind::Left);
+        children += self.spawn_player(
+            ctx,
+            V2::new(400.0, 350.0),
+            controls.left.clone(),
+            PlayerKind::Left,
+            &rules,
+        );
         children += self.spawn_player(
             ctx,
             V2::new(600.0, 350.0),
-            Keyset::ArrowKeys,
+            controls.right.clone(),
             PlayerKind::Right,
+            &rules,
         );
 
+        let stage_pos = V2::new(350.0, 525.0);
+        let stage_size = V2::new(676.0, 110.0);
         children += spawn!(
             ctx,
-            RigidBody::new()
-                .with_pos(V2::new(350.0, 525.0))
-                .with_size(V2::new(676.0, 110.0)),
+            RigidBody::new().with_pos(stage_pos).with_size(stage_size),
             SolidCollider::new(),
         );
+        for ledge in spawn_ledges(ctx, stage_pos, stage_size) {
+            children += ledge;
+        }
 
+        let block_pos = V2::new(126.0, 162.0);
+        let block_size = V2::new(180.0, 204.0);
         children += spawn!(
             ctx,
-            RigidBody::new()
-                .with_pos(V2::new(126.0, 162.0))
-                .with_size(V2::new(180.0, 204.0)),
+            RigidBody::new().with_pos(block_pos).with_size(block_size),
             SolidCollider::new(),
         );
+        for ledge in spawn_ledges(ctx, block_pos, block_size) {
+            children += ledge;
+        }
 
         children += spawn!(
             ctx,
@@ -123,6 +153,19 @@ impl System for GameSystem {
             ShallowCollider::new().with_direction(QuadDirection::Top),
         );
 
+        let max_offset_from_screen = 200.0;
+        children += spawn!(
+            ctx,
+            RigidBody::new()
+                .with_pos(V2::new(-max_offset_from_screen, -max_offset_from_screen))
+                .with_size(V2::new(
+                    1280.0 + max_offset_from_screen * 2.0,
+                    720.0 + max_offset_from_screen * 2.0
+                )),
+            Trigger::new().mask(DEFAULT_LAYER),
+            BlastZone,
+        );
+
         let sound_player = ctx.select_one::<SoundPlayer>();
         sound_player.play_music("assets/sounds/theme_2.ogg");
 
@@ -154,6 +197,8 @@ impl System for GameSystem {
         }
         let heroes_on_board = query_one!(ctx, HeroesOnBoard);
         ctx.despawn(heroes_on_board);
+        let rules = query_one!(ctx, MatchRules);
+        ctx.despawn(rules);
         ctx.add_system(crate::main_menu::MainMenuSystem);
         Ok(())
     }
@@ -202,6 +247,17 @@ impl CollisionResolver for BouncingCollider {
             }
         }
     }
+
+    fn resolve_normal(&self, body: &mut RigidBody, normal: V2) {
+        if body.vel.len() <= 1200.0 {
+            return DefaultResolver.resolve_normal(body, normal);
+        }
+        // reflect off angled walls, keeping half the approaching speed
+        let approach = body.vel.dot(normal);
+        if approach < 0.0 {
+            body.vel += normal.extend(-approach * 1.5);
+        }
+    }
 }
 
 impl GameSystem {
@@ -209,8 +265,9 @@ impl GameSystem {
         &self,
         ctx: &mut engine::Context,
         pos: V2,
-        keyset: Keyset,
+        bindings: Bindings,
         kind: PlayerKind,
+        rules: &MatchRules,
     ) -> engine::Id {
         let scale = 1.0;
         let pixel_ratio = 4.0;
@@ -219,27 +276,38 @@ impl GameSystem {
         let texture = self.hero_texture(ctx, &hero.kind);
 
         let factor = scale * pixel_ratio;
+        let gravity_scale = crate::hero_info::HeroInfo::from(&hero.kind).gravity_scale;
+        let mass = 1.0 + hero.defence_points as f64 / 24.0;
+        let shield = Shield::from_defence(hero.defence_points);
         spawn!(
             ctx,
             Sprite::new(texture).layer(1),
-            Hitbox {
-                size: V2::new(24.0 * factor, 28.0 * factor),
-                offset: V2::new(4.0 * factor, 2.0 * factor)
-            },
+            Trigger::new()
+                .size(V2::new(24.0 * factor, 28.0 * factor))
+                .offset(V2::new(4.0 * factor, 2.0 * factor))
+                .layer(HITBOX_LAYER)
+                .mask(HURTBOX_LAYER),
             RigidBody::new()
                 .with_pos(pos)
                 .with_size(V2::new(32.0 * factor, 32.0 * factor))
                 .with_gravity()
+                .with_gravity_scale(gravity_scale)
                 .with_drag(),
-            SolidCollider::new().resolving(BouncingCollider),
+            SolidCollider::new()
+                .resolving(BouncingCollider)
+                .with_body_collisions()
+                .mass(mass)
+                .restitution(0.2),
             //.resolving(DefaultResolver),
             Player {
+                handicap: rules.handicap(&kind),
                 kind,
                 hero,
                 damage_taken: 0.0,
-                lives: 3,
+                lives: rules.stocks,
+                stamina: rules.stamina,
             },
-            PlayerInteraction::new(keyset, 0.0),
+            PlayerInteraction::new(bindings, 0.0).with_shield(shield),
             Victim::default()
         )
     }
@@ -270,20 +338,22 @@ impl System for DebugDrawer {
             let body = ctx.select::<RigidBody>(id).clone();
             self.draw_outline(ctx, body.pos, body.size, 2.0, (0, 125, 255))?;
         }
+        for id in query!(ctx, RigidBody, PolygonCollider) {
+            let body = ctx.select::<RigidBody>(id).clone();
+            let polygon = ctx.select::<PolygonCollider>(id).polygon(&body);
+            for point in polygon.points {
+                let size = V2::new(6.0, 6.0);
+                self.draw_outline(ctx, point - size.div_comps(2.0), size, 2.0, (0, 125, 255))?;
+            }
+        }
         for id in query!(ctx, RigidBody, Hurtbox) {
             let body = ctx.select::<RigidBody>(id).clone();
             self.draw_outline(ctx, body.pos, body.size, 2.0, (255, 0, 0))?;
         }
-        for id in query!(ctx, RigidBody, Hitbox) {
+        for id in query!(ctx, RigidBody, Trigger) {
             let body = ctx.select::<RigidBody>(id).clone();
-            let hitbox = ctx.select::<Hitbox>(id).clone();
-            self.draw_outline(
-                ctx,
-                body.pos + hitbox.offset,
-                hitbox.size,
-                2.0,
-                (0, 255, 125),
-            )?;
+            let (pos, size) = ctx.select::<Trigger>(id).bounds(&body);
+            self.draw_outline(ctx, pos, size, 2.0, (0, 255, 125))?;
         }
         Ok(())
     }
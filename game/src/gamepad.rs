@@ -0,0 +1,59 @@
+use engine::{query, Context};
+
+use crate::{
+    player::{Player, PlayerKind},
+    player_interaction::PlayerInteraction,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gamepad(pub engine::Id);
+
+impl Gamepad {
+    pub fn rumble(&self, ctx: &mut Context, strength: f64, duration: f64) {
+        if let Err(err) = ctx.controller_rumble(self.0, strength, strength, duration) {
+            println!("unable to rumble controller {}: {err}", self.0);
+        }
+    }
+}
+
+/// Unassigns disconnected controllers and hands newly connected ones to
+/// the left hero first, then the right hero.
+pub fn assign_gamepads(ctx: &mut Context) {
+    let active = ctx.active_controllers().collect::<Vec<_>>();
+
+    let mut players = query!(ctx, PlayerInteraction, Player)
+        .into_iter()
+        .map(|id| (id, ctx.select::<Player>(id).kind.clone()))
+        .collect::<Vec<_>>();
+    players.sort_by_key(|(_, kind)| match kind {
+        PlayerKind::Left => 0,
+        PlayerKind::Right => 1,
+    });
+
+    for (id, _) in &players {
+        let interaction = ctx.select::<PlayerInteraction>(*id);
+        if interaction
+            .gamepad
+            .as_ref()
+            .is_some_and(|gamepad| !active.contains(&gamepad.0))
+        {
+            interaction.gamepad = None;
+        }
+    }
+
+    for controller_id in active {
+        let assigned = players.iter().any(|(id, _)| {
+            ctx.select::<PlayerInteraction>(*id).gamepad == Some(Gamepad(controller_id))
+        });
+        if assigned {
+            continue;
+        }
+        let Some((id, _)) = players
+            .iter()
+            .find(|(id, _)| ctx.select::<PlayerInteraction>(*id).gamepad.is_none())
+        else {
+            break;
+        };
+        ctx.select::<PlayerInteraction>(*id).gamepad = Some(Gamepad(controller_id));
+    }
+}
@@ -0,0 +1,90 @@
+use engine::{collision::rects_collide, rigid_body::RigidBody, Id, V2};
+
+use crate::timer::Timer;
+
+#[derive(Clone)]
+pub enum GrabState {
+    Free,
+    /// Holding an opponent until a throw or the timer is done.
+    Holding {
+        target: Id,
+        timer: Timer,
+        /// Can't pummel again until done.
+        pummel: Timer,
+    },
+    /// Held by another player, who moves the body.
+    Held {
+        by: Id,
+    },
+}
+
+impl GrabState {
+    pub fn holding(target: Id) -> Self {
+        GrabState::Holding {
+            target,
+            timer: Timer::new(1.5),
+            pummel: Timer::new(0.0),
+        }
+    }
+
+    pub fn held(&self) -> bool {
+        matches!(self, GrabState::Held { .. })
+    }
+}
+
+/// Area in front of the body where opponents can be grabbed.
+pub fn grab_range(body: &RigidBody, facing: f64) -> (V2, V2) {
+    let size = V2::new(body.size.x * 0.5, body.size.y);
+    let x = if facing < 0.0 {
+        body.pos.x - size.x
+    } else {
+        body.pos.x + body.size.x
+    };
+    (V2::new(x, body.pos.y), size)
+}
+
+pub fn in_grab_range(body: &RigidBody, facing: f64, target: &RigidBody) -> bool {
+    let (pos, size) = grab_range(body, facing);
+    rects_collide(pos, size, target.pos, target.size)
+}
+
+/// Where the holder keeps a held body, in front of it with their feet level.
+/// Only touching it, so body collisions don't push the two apart.
+pub fn held_pos(body: &RigidBody, facing: f64, held_size: V2) -> V2 {
+    let x = if facing < 0.0 {
+        body.pos.x - held_size.x
+    } else {
+        body.pos.x + body.size.x
+    };
+    V2::new(x, body.pos.y + body.size.y - held_size.y)
+}
+
+#[test]
+fn test_grab_range_follows_facing() {
+    let body = RigidBody::new()
+        .with_pos(V2::new(100.0, 0.0))
+        .with_size(V2::new(50.0, 50.0));
+    let right = RigidBody::new()
+        .with_pos(V2::new(160.0, 0.0))
+        .with_size(V2::new(50.0, 50.0));
+    let left = RigidBody::new()
+        .with_pos(V2::new(40.0, 0.0))
+        .with_size(V2::new(50.0, 50.0));
+    assert!(in_grab_range(&body, 1.0, &right));
+    assert!(!in_grab_range(&body, 1.0, &left));
+    assert!(in_grab_range(&body, -1.0, &left));
+    assert!(!in_grab_range(&body, -1.0, &right));
+}
+
+#[test]
+fn test_held_body_doesnt_overlap_holder() {
+    let body = RigidBody::new()
+        .with_pos(V2::new(100.0, 0.0))
+        .with_size(V2::new(128.0, 128.0));
+    let held_size = V2::new(128.0, 128.0);
+    for facing in [-1.0, 1.0] {
+        let pos = held_pos(&body, facing, held_size);
+        assert!(!rects_collide(body.pos, body.size, pos, held_size));
+        assert_eq!(pos.y, body.pos.y);
+    }
+}
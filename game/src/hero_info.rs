@@ -6,6 +6,8 @@ pub struct HeroInfo {
     pub base_stats: shared::HeroStats,
     pub texture_path: PathBuf,
     pub name: String,
+    /// Heavier heroes fall faster.
+    pub gravity_scale: f64,
 }
 
 impl From<HeroKind> for HeroInfo {
@@ -31,10 +33,15 @@ impl From<&HeroKind> for HeroInfo {
             Speed => PathBuf::from("./assets/heroes/speed.png"),
             Tankie => PathBuf::from("./assets/heroes/tankie.png"),
         };
+        let gravity_scale = match value {
+            Tankie => 1.2,
+            Centrist | Strong | Speed => 1.0,
+        };
         Self {
             base_stats,
             texture_path,
             name,
+            gravity_scale,
         }
     }
 }
@@ -1,9 +1,16 @@
-use engine::{query, Component, Context, DrawTextureOpts, Error, System, V2};
+use engine::{
+    query, rigid_body::RigidBody, Component, Context, DrawTextureOpts, Error, System, V2,
+};
 use shared::HeroKind;
 
 use crate::{
     game::Game,
+    hurtbox::{Combo, Victim},
+    match_rules::{clock_text, MatchState},
     player::{Player, PlayerKind},
+    player_interaction::{Charging, PlayerInteraction},
+    shield::Shield,
+    timer::Timer,
     FONT,
 };
 
@@ -15,6 +22,49 @@ impl System for HudSystem {
             let player = ctx.select::<Player>(id).clone();
             draw_hud(ctx, &player);
         }
+        for id in query!(ctx, MatchState).clone() {
+            let state = ctx.select::<MatchState>(id).clone();
+            draw_clock(ctx, &state);
+        }
+        for id in query!(ctx, Player, Victim).clone() {
+            let kind = ctx.select::<Player>(id).kind.clone();
+            let combo = ctx.select::<Victim>(id).combo.clone();
+            if combo.hits >= 2 {
+                draw_combo_counter(ctx, &kind, &combo);
+            }
+        }
+        for id in query!(ctx, ComboSummary).clone() {
+            let summary = ctx.select::<ComboSummary>(id);
+            summary.timer.update(delta);
+            let summary = summary.clone();
+            if summary.timer.done() {
+                ctx.despawn(id);
+                continue;
+            }
+            summary.draw(ctx);
+        }
+        for id in query!(ctx, DamageNumber).clone() {
+            let number = ctx.select::<DamageNumber>(id);
+            number.timer.update(delta);
+            let number = number.clone();
+            if number.timer.done() {
+                ctx.despawn(id);
+                continue;
+            }
+            number.draw(ctx);
+        }
+        for id in query!(ctx, PlayerInteraction, RigidBody).clone() {
+            let PlayerInteraction {
+                charging, shield, ..
+            } = ctx.select::<PlayerInteraction>(id).clone();
+            let body = ctx.select::<RigidBody>(id).clone();
+            if let Some(charging) = charging {
+                draw_charge(ctx, &charging, &body);
+            }
+            if shield.raised() {
+                draw_shield(ctx, &shield, &body);
+            }
+        }
         for id in query!(ctx, TrashTalk).clone() {
             let trash_talk = ctx.select::<TrashTalk>(id);
             trash_talk.text_cycle_clock += delta;
@@ -39,6 +89,12 @@ impl System for HudSystem {
         for id in query!(ctx, ReturnToMenu).clone() {
             ctx.despawn(id);
         }
+        for id in query!(ctx, ComboSummary).clone() {
+            ctx.despawn(id);
+        }
+        for id in query!(ctx, DamageNumber).clone() {
+            ctx.despawn(id);
+        }
         Ok(())
     }
 }
@@ -122,6 +178,100 @@ impl TrashTalk {
     }
 }
 
+/// Floats up from where a hit landed and fades out.
+#[derive(Clone, Component)]
+pub struct DamageNumber {
+    pub damage: f64,
+    pub pos: V2,
+    pub color: (u8, u8, u8),
+    pub timer: Timer,
+}
+
+impl DamageNumber {
+    pub fn new(damage: f64, pos: V2, color: (u8, u8, u8)) -> Self {
+        Self {
+            damage,
+            pos,
+            color,
+            timer: Timer::new(0.8),
+        }
+    }
+
+    fn draw(&self, ctx: &mut Context) {
+        let progress = self.timer.time_passed() / self.timer.duration();
+        let rise = 40.0;
+        let font = ctx.load_font(FONT, 28).unwrap();
+        let text = format!("{:.0}", self.damage);
+        let text = ctx.render_text(font, text, self.color).unwrap();
+        let pos = self.pos - V2::new(text.size.x / 2.0, text.size.y + rise * progress);
+        ctx.draw_texture(
+            text.texture,
+            pos,
+            DrawTextureOpts::new().opacity(1.0 - progress),
+        )
+        .unwrap();
+    }
+}
+
+/// Shown under the victim's stats for a while after a combo ends.
+#[derive(Clone, Component)]
+pub struct ComboSummary {
+    pub victim: PlayerKind,
+    pub combo: Combo,
+    pub timer: Timer,
+}
+
+impl ComboSummary {
+    pub fn new(victim: PlayerKind, combo: Combo) -> Self {
+        Self {
+            victim,
+            combo,
+            timer: Timer::new(2.0),
+        }
+    }
+
+    fn draw(&self, ctx: &mut Context) {
+        let fade_time = 0.5;
+        let opacity = (self.timer.time_remaining() / fade_time).min(1.0);
+        let font = ctx.load_font(FONT, 24).unwrap();
+        let text = format!(
+            "{} hit combo, {:.0} damage",
+            self.combo.hits, self.combo.damage
+        );
+        let text = ctx.render_text(font, text, (255, 255, 255)).unwrap();
+        // below the counter of a new combo, which is at most 56 px high
+        let counter_height = 56.0 + 8.0;
+        let pos = combo_pos(&self.victim, text.size.x).add_y(counter_height);
+        ctx.draw_texture(text.texture, pos, DrawTextureOpts::new().opacity(opacity))
+            .unwrap();
+    }
+}
+
+/// Below the stats of the player, aligned with the screen edge.
+fn combo_pos(kind: &PlayerKind, width: f64) -> V2 {
+    let y = 8.0 + 88.0 + 8.0;
+    match kind {
+        PlayerKind::Left => V2::new(8.0, y),
+        PlayerKind::Right => V2::new(1280.0 - width - 8.0, y),
+    }
+}
+
+/// Hits of an ongoing combo, grown a bit with every hit.
+fn draw_combo_counter(ctx: &mut Context, victim: &PlayerKind, combo: &Combo) {
+    let font_size = std::cmp::min(32 + combo.hits as u16 * 2, 56);
+    let font = ctx.load_font(FONT, font_size).unwrap();
+    let text = format!("{} hits", combo.hits);
+    let color = merge_colors(
+        (255, 255, 255),
+        (255, 200, 0),
+        (combo.hits as f64 / 10.0).min(1.0),
+    );
+    let text = ctx.render_text(font, text, color).unwrap();
+    let pos = combo_pos(victim, text.size.x);
+    ctx.draw_texture(text.texture, pos, DrawTextureOpts::new())
+        .unwrap();
+}
+
 fn linear_interpolation(current: u8, next: u8, percentage: f64) -> u8 {
     (f64::from(current) * (1.0 - percentage) + f64::from(next) * percentage) as u8
 }
@@ -234,4 +384,80 @@ fn draw_hud(ctx: &mut Context, player: &Player) {
     };
 
     draw_player_stats(ctx, player, avatar_pos, avatar_size, text_pos);
+
+    if let Some(stamina) = player.stamina {
+        let font = ctx.load_font(FONT, 24).unwrap();
+        let text = format!("{:.0} HP", stamina.max(0.0));
+        let text = ctx.render_text(font, text, border_color).unwrap();
+        let pos = match player.kind {
+            PlayerKind::Left => border_pos + V2::new(stats_size.x + 8.0, 0.0),
+            PlayerKind::Right => border_pos - V2::new(text.size.x + 8.0, 0.0),
+        };
+        ctx.draw_texture(text.texture, pos, DrawTextureOpts::new())
+            .unwrap();
+    }
+}
+
+/// Time left at the top of the screen, replaced by a notice in sudden death.
+fn draw_clock(ctx: &mut Context, state: &MatchState) {
+    let (text, color) = match &state.timer {
+        Some(timer) => (clock_text(timer.time_remaining()), (255, 255, 255)),
+        None if state.sudden_death => ("Sudden death".to_string(), (255, 60, 60)),
+        None => return,
+    };
+    let font = ctx.load_font(FONT, 36).unwrap();
+    let text = ctx.render_text(font, text, color).unwrap();
+    ctx.draw_texture(
+        text.texture,
+        V2::new((1280.0 - text.size.x) / 2.0, 8.0),
+        DrawTextureOpts::new(),
+    )
+    .unwrap();
+}
+
+/// Bar above a player charging an attack, going from white to red.
+fn draw_charge(ctx: &mut Context, charging: &Charging, body: &RigidBody) {
+    let size = V2::new(64.0, 8.0);
+    let pos = V2::new(body.pos.x + (body.size.x - size.x) / 2.0, body.pos.y - 20.0);
+    let progress = charging.progress();
+    let color = merge_colors((255, 255, 255), (255, 0, 0), progress);
+    ctx.draw_rect_transparent(
+        (0, 0, 0),
+        pos.x as i32 - 2,
+        pos.y as i32 - 2,
+        size.x as u32 + 4,
+        size.y as u32 + 4,
+        100,
+    )
+    .unwrap();
+    ctx.draw_rect(
+        color,
+        pos.x as i32,
+        pos.y as i32,
+        (size.x * progress) as u32,
+        size.y as u32,
+    )
+    .unwrap();
+}
+
+/// Bubble around a shielding player, shrinking and going red as it wears
+/// down. Flashes white during the parry window.
+fn draw_shield(ctx: &mut Context, shield: &Shield, body: &RigidBody) {
+    let fraction = shield.fraction();
+    let color = if shield.parrying() {
+        (255, 255, 255)
+    } else {
+        merge_colors((255, 0, 0), (80, 160, 255), fraction)
+    };
+    let size = body.size.extend(0.8 + 0.6 * fraction);
+    let pos = body.pos + (body.size - size).div_comps(2.0);
+    ctx.draw_rect_transparent(
+        color,
+        pos.x as i32,
+        pos.y as i32,
+        size.x as u32,
+        size.y as u32,
+        90,
+    )
+    .unwrap();
 }
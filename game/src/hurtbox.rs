@@ -1,33 +1,33 @@
 use std::rc::Rc;
 
+use engine::collision::{rects_collide, Trigger};
+use engine::physics::Rect;
 use engine::rigid_body::RigidBody;
-use engine::{query, SolidCollider, Component, V2};
+use engine::{clamp, max, query, spawn, Component, SolidCollider, V2};
 use engine::{Context, Error, System};
 
+use crate::hud::{player_damage_color, ComboSummary, DamageNumber};
 use crate::player::Player;
-use crate::player_interaction::{DodgeState, PlayerInteraction};
+use crate::player_interaction::PlayerInteraction;
+use crate::shield::Shield;
 use crate::sprite_renderer::Sprite;
 use crate::timer::Timer;
 
-#[derive(Default, Clone)]
-pub enum HurtDirection {
-    #[default]
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-#[derive(Component, Default, Clone)]
-pub struct Hitbox {
-    pub size: V2,
-    pub offset: V2,
-}
+/// Trigger layer of the part of a victim that can be hit.
+pub const HITBOX_LAYER: u32 = 1 << 1;
+/// Trigger layer of hurtboxes, only overlapping hitboxes.
+pub const HURTBOX_LAYER: u32 = 1 << 2;
 
 pub struct Outcome {
     pub damage: f64,
     pub delta_vel: V2,
     pub stun_time: Option<f64>,
+    /// Seconds the victim, and the attacker unless `attacker_hitlag` is
+    /// false, freeze when the hit lands.
+    pub hitlag: f64,
+    pub attacker_hitlag: bool,
+    /// Knockback strong enough to send the victim tumbling.
+    pub tumble: bool,
 }
 
 pub trait HurtboxProfile {
@@ -40,47 +40,130 @@ pub trait HurtboxProfile {
     ) -> Outcome;
 }
 
+/// Flying hurtbox, despawned when hitting a solid collider, after its
+/// range or after hitting more victims than it pierces.
+#[derive(Clone)]
+pub struct ProjectileState {
+    pub origin: V2,
+    pub range: Option<f64>,
+    pub pierce: u32,
+    pub hits: u32,
+    pub reflectable: bool,
+}
+
 #[derive(Component, Clone)]
 pub struct Hurtbox {
     pub owner: Option<engine::Id>,
     pub timer: Timer,
+    /// Seconds before the hurtbox starts hurting.
+    pub startup: f64,
+    /// Position relative to the owner, for hurtboxes attached to it.
+    pub follow: Option<V2>,
     pub textures: Vec<engine::Texture>,
+    /// Hurting part of each frame, relative to the body. The whole body
+    /// when not set.
+    pub frame_hurtboxes: Vec<Option<Rect>>,
+    pub projectile: Option<ProjectileState>,
     pub profile: Rc<dyn HurtboxProfile>,
 }
 
+impl Hurtbox {
+    pub fn active(&self) -> bool {
+        self.timer.time_passed() >= self.startup
+    }
+
+    pub fn frame(&self) -> usize {
+        let progress = self.timer.time_passed() / self.timer.duration();
+        let frame = (progress * self.textures.len() as f64).floor() as usize;
+        std::cmp::min(frame, self.textures.len() - 1)
+    }
+}
+
+/// Freeze after a hit. The body resumes with `vel` plus the knockback,
+/// which the victim can influence with the held direction.
+#[derive(Clone)]
+pub struct Hitlag {
+    pub timer: Timer,
+    pub vel: V2,
+    pub knockback: V2,
+}
+
+/// Hits landed while the victim was still stunned from the previous one.
+#[derive(Clone, Default)]
+pub struct Combo {
+    pub hits: u32,
+    pub damage: f64,
+}
+
 #[derive(Component, Default, Clone)]
 pub struct Victim {
     pub hurt_by: Vec<engine::Id>,
     pub stunned: Option<f64>,
+    pub hitlag: Option<Hitlag>,
+    /// Sent flying by a strong hit, until landing or acting after the stun.
+    pub tumbling: bool,
+    pub combo: Combo,
 }
 
-fn rects_collide(pos_a: V2, size_a: V2, pos_b: V2, size_b: V2) -> bool {
-    pos_a.x < pos_b.x + size_b.x
-        && pos_a.x + size_a.x > pos_b.x
-        && pos_a.y < pos_b.y + size_b.y
-        && pos_a.y + size_a.y > pos_b.y
+impl Victim {
+    /// Hits during hitlag add their knockback and extend the freeze.
+    pub fn freeze(&mut self, time: f64, vel: V2, knockback: V2) {
+        self.hitlag = Some(match self.hitlag.take() {
+            Some(hitlag) => Hitlag {
+                timer: Timer::new(max(time, hitlag.timer.time_remaining())),
+                vel: hitlag.vel,
+                knockback: hitlag.knockback + knockback,
+            },
+            None => Hitlag {
+                timer: Timer::new(time),
+                vel,
+                knockback,
+            },
+        });
+    }
 }
 
 pub struct HurtboxSystem(pub u64);
 impl System for HurtboxSystem {
     fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
-        for id in query!(ctx, Victim) {
+        for id in query!(ctx, Victim, RigidBody, PlayerInteraction, Player) {
+            self.update_hitlag(ctx, delta, id);
             let victim = ctx.select::<Victim>(id);
-            self.update_victim_stun_timer(victim, delta);
+            if victim.hitlag.is_none() {
+                self.update_victim_stun_timer(victim, delta);
+            }
+            self.end_combo(ctx, id);
         }
         self.despawn_expired_hurtboxes(ctx, delta);
-        for hurtbox_id in query!(ctx, Hurtbox, RigidBody).clone() {
+        self.follow_owners(ctx);
+        self.update_projectiles(ctx);
+        for id in query!(ctx, Hurtbox, Trigger) {
+            let hurtbox = ctx.select::<Hurtbox>(id).clone();
+            let trigger = ctx.select::<Trigger>(id);
+            self.update_frame_hurtbox(&hurtbox, trigger);
+        }
+
+        let victims = query!(
+            ctx,
+            PlayerInteraction,
+            RigidBody,
+            SolidCollider,
+            Player,
+            Victim,
+            Trigger
+        );
+
+        for hurtbox_id in query!(ctx, Hurtbox, RigidBody, Trigger).clone() {
             let hurtbox_body = ctx.select::<RigidBody>(hurtbox_id).clone();
             let hurtbox = ctx.select::<Hurtbox>(hurtbox_id).clone();
-            for victim_id in query!(
-                ctx,
-                PlayerInteraction,
-                RigidBody,
-                SolidCollider,
-                Player,
-                Victim,
-                Hitbox
-            ) {
+            if !hurtbox.active() {
+                continue;
+            }
+            let overlapping = ctx.select::<Trigger>(hurtbox_id).overlapping().to_vec();
+            for victim_id in overlapping {
+                if !victims.contains(&victim_id) {
+                    continue;
+                }
                 if hurtbox.owner.is_some_and(|owner| owner == victim_id) {
                     continue;
                 };
@@ -89,28 +172,20 @@ impl System for HurtboxSystem {
                     continue;
                 }
 
-                let hitbox = ctx.select::<Hitbox>(victim_id).clone();
-                let victim_body = ctx.select::<RigidBody>(victim_id).clone();
-
-                if !rects_collide(
-                    hurtbox_body.pos,
-                    hurtbox_body.size,
-                    victim_body.pos + hitbox.offset,
-                    hitbox.size,
-                ) {
+                if ctx.select::<PlayerInteraction>(victim_id).invulnerable() {
                     continue;
-                };
+                }
 
-                let dodge_state = ctx
-                    .select::<PlayerInteraction>(victim_id)
-                    .clone()
-                    .dodge_state;
+                self.hurt_victim(hurtbox_id, &hurtbox, ctx, victim_id, &hurtbox_body);
 
-                if matches!(dodge_state, DodgeState::Dodging(_)) {
+                let Some(projectile) = &mut ctx.select::<Hurtbox>(hurtbox_id).projectile else {
                     continue;
+                };
+                projectile.hits += 1;
+                if projectile.hits > projectile.pierce {
+                    ctx.despawn(hurtbox_id);
+                    break;
                 }
-
-                self.hurt_victim(hurtbox_id, &hurtbox, ctx, victim_id, &hurtbox_body);
             }
         }
         for id in query!(ctx, Hurtbox, Sprite).clone() {
@@ -131,44 +206,36 @@ impl HurtboxSystem {
         victim_id: u64,
         hurtbox_body: &RigidBody,
     ) {
-        let attacker = hurtbox.owner.map(|id| ctx.select::<Player>(id).clone());
-
-        let attacker_strength = attacker
-            .as_ref()
-            .map(|a| a.hero.strength_points)
-            .unwrap_or(0);
-
-        let victim_body = ctx.select::<RigidBody>(victim_id).clone();
-        let victim = ctx.select::<Player>(victim_id);
-        let victim_defence = victim.hero.defence_points;
-
-        let Outcome {
-            damage,
-            delta_vel,
-            stun_time,
-        } = hurtbox
-            .profile
-            .outcome(victim, attacker.as_ref(), hurtbox_body, &victim_body);
-
-        let max_points = 24.0;
-        let damage_multiplier = 1.0 + attacker_strength as f64 / (max_points * 2.0)
-            - (victim_defence as f64 + 1.0) / (max_points * 2.0);
-        let damage = damage * damage_multiplier;
-        let victim = ctx.select::<Victim>(victim_id);
-        victim.hurt_by.push(hurtbox_id);
-        victim.stunned = stun_time;
-
-        let victim_body = ctx.select::<RigidBody>(victim_id);
+        ctx.select::<Victim>(victim_id).hurt_by.push(hurtbox_id);
 
-        victim_body.vel += delta_vel;
-
-        let player = ctx.select::<Player>(victim_id);
+        let counter = ctx.select::<PlayerInteraction>(victim_id).counter();
+        if let (Some(counter), Some(owner)) = (counter, hurtbox.owner) {
+            ctx.select::<PlayerInteraction>(victim_id).current_move = None;
+            let counter_body = ctx.select::<RigidBody>(victim_id).clone();
+            hit(ctx, counter.as_ref(), Some(victim_id), owner, &counter_body);
+            return;
+        }
 
-        player.damage_taken += damage;
+        hit(
+            ctx,
+            hurtbox.profile.as_ref(),
+            hurtbox.owner,
+            victim_id,
+            hurtbox_body,
+        );
     }
 
     fn despawn_expired_hurtboxes(&self, ctx: &mut Context, delta: f64) {
         for hurtbox_id in query!(ctx, Hurtbox) {
+            let Hurtbox {
+                owner, projectile, ..
+            } = ctx.select::<Hurtbox>(hurtbox_id).clone();
+            // attacks freeze along with their owner
+            let frozen = projectile.is_none()
+                && owner.is_some_and(|owner| ctx.select::<Victim>(owner).hitlag.is_some());
+            if frozen {
+                continue;
+            }
             let hurtbox = ctx.select::<Hurtbox>(hurtbox_id);
             hurtbox.timer.update(delta);
             if hurtbox.timer.done() {
@@ -178,14 +245,131 @@ impl HurtboxSystem {
         }
     }
 
+    fn update_projectiles(&self, ctx: &mut Context) {
+        let players = query!(ctx, PlayerInteraction);
+        let solids = query!(ctx, RigidBody, SolidCollider);
+        let hurtboxes = query!(ctx, Hurtbox, RigidBody, Trigger);
+        // despawned after the loop, reflections still look at every hurtbox
+        let mut expired = Vec::new();
+        for id in hurtboxes.clone() {
+            let Some(projectile) = ctx.select::<Hurtbox>(id).projectile.clone() else {
+                continue;
+            };
+            let body = ctx.select::<RigidBody>(id).clone();
+            let out_of_range = projectile
+                .range
+                .is_some_and(|range| (body.pos - projectile.origin).len() > range);
+            let hit_solid = ctx
+                .select::<Trigger>(id)
+                .overlapping()
+                .iter()
+                .any(|other| solids.contains(other) && !players.contains(other));
+            if out_of_range || hit_solid {
+                expired.push(id);
+                continue;
+            }
+            if projectile.reflectable {
+                self.reflect_projectile(ctx, id, &hurtboxes);
+            }
+        }
+        for id in expired {
+            ctx.despawn(id);
+        }
+    }
+
+    /// Sends the projectile back when an active attack of someone else
+    /// than its owner hits it.
+    fn reflect_projectile(&self, ctx: &mut Context, id: u64, hurtboxes: &[u64]) {
+        let hurtbox = ctx.select::<Hurtbox>(id).clone();
+        let body = ctx.select::<RigidBody>(id).clone();
+        let reflector = hurtboxes.iter().copied().find_map(|other_id| {
+            let other = ctx.select::<Hurtbox>(other_id).clone();
+            let other_body = ctx.select::<RigidBody>(other_id);
+            let reflects = other.projectile.is_none()
+                && other.active()
+                && other.owner.is_some()
+                && other.owner != hurtbox.owner
+                && rects_collide(body.pos, body.size, other_body.pos, other_body.size);
+            reflects.then_some(other.owner).flatten()
+        });
+        let Some(reflector) = reflector else {
+            return;
+        };
+        let hurtbox = ctx.select::<Hurtbox>(id);
+        hurtbox.owner = Some(reflector);
+        if let Some(projectile) = &mut hurtbox.projectile {
+            projectile.origin = body.pos;
+            projectile.hits = 0;
+        }
+        ctx.select::<RigidBody>(id).vel = body.vel.reverse();
+        let sprite = ctx.select::<Sprite>(id);
+        sprite.flip_horizontal = !sprite.flip_horizontal;
+    }
+
+    fn follow_owners(&self, ctx: &mut Context) {
+        for id in query!(ctx, Hurtbox, RigidBody) {
+            let hurtbox = ctx.select::<Hurtbox>(id).clone();
+            let (Some(owner), Some(offset)) = (hurtbox.owner, hurtbox.follow) else {
+                continue;
+            };
+            let owner_pos = ctx.select::<RigidBody>(owner).pos;
+            ctx.select::<RigidBody>(id).pos = owner_pos + offset;
+        }
+    }
+
+    fn update_frame_hurtbox(&self, hurtbox: &Hurtbox, trigger: &mut Trigger) {
+        match hurtbox
+            .frame_hurtboxes
+            .get(hurtbox.frame())
+            .copied()
+            .flatten()
+        {
+            Some(rect) => {
+                trigger.offset = rect.pos;
+                trigger.size = Some(rect.size);
+            }
+            None => {
+                trigger.offset = V2::new(0.0, 0.0);
+                trigger.size = None;
+            }
+        }
+    }
+
     fn draw_hurtbox_animation(&self, hurtbox: Hurtbox, sprite: &mut Sprite) {
-        let texture = hurtbox.textures[std::cmp::min(
-            ((hurtbox.timer.time_passed() / hurtbox.timer.duration())
-                * hurtbox.textures.len() as f64)
-                .floor() as usize,
-            hurtbox.textures.len(),
-        )];
-        sprite.texture = texture;
+        sprite.texture = hurtbox.textures[hurtbox.frame()];
+    }
+
+    /// Keeps frozen fighters still, then sends them off with their
+    /// knockback influenced by the direction they hold.
+    fn update_hitlag(&self, ctx: &mut Context, delta: f64, id: u64) {
+        let Some(hitlag) = &mut ctx.select::<Victim>(id).hitlag else {
+            return;
+        };
+        hitlag.timer.update(delta);
+        let hitlag = hitlag.clone();
+        if !hitlag.timer.done() {
+            ctx.select::<RigidBody>(id).vel = V2::new(0.0, 0.0);
+            return;
+        }
+        ctx.select::<Victim>(id).hitlag = None;
+        let input = &ctx.select::<PlayerInteraction>(id).input;
+        let held = V2::new(input.horizontal(), input.vertical());
+        let max_influence = 15.0;
+        let knockback = directional_influence(hitlag.knockback, held, max_influence);
+        ctx.select::<RigidBody>(id).vel = hitlag.vel + knockback;
+    }
+
+    /// Combos end with the stun, leaving a summary in the HUD.
+    fn end_combo(&self, ctx: &mut Context, id: u64) {
+        let victim = ctx.select::<Victim>(id);
+        if victim.stunned.is_some() || victim.combo.hits == 0 {
+            return;
+        }
+        let combo = std::mem::take(&mut victim.combo);
+        if combo.hits >= 2 {
+            let kind = ctx.select::<Player>(id).kind.clone();
+            spawn!(ctx, ComboSummary::new(kind, combo));
+        }
     }
 
     fn update_victim_stun_timer(&self, victim: &mut Victim, delta: f64) {
@@ -197,3 +381,167 @@ impl HurtboxSystem {
         }
     }
 }
+
+/// Applies the outcome of a hit by the profile to the victim, also used
+/// for throws.
+pub fn hit(
+    ctx: &mut Context,
+    profile: &dyn HurtboxProfile,
+    owner: Option<engine::Id>,
+    victim_id: u64,
+    hurtbox_body: &RigidBody,
+) {
+    let attacker = owner.map(|id| ctx.select::<Player>(id).clone());
+
+    let attacker_strength = attacker
+        .as_ref()
+        .map(|a| a.hero.strength_points)
+        .unwrap_or(0);
+
+    let victim_body = ctx.select::<RigidBody>(victim_id).clone();
+    let victim = ctx.select::<Player>(victim_id);
+    let victim_defence = victim.hero.defence_points;
+
+    let Outcome {
+        damage,
+        delta_vel,
+        stun_time,
+        hitlag,
+        attacker_hitlag,
+        tumble,
+    } = profile.outcome(victim, attacker.as_ref(), hurtbox_body, &victim_body);
+
+    // armor takes the damage without being knocked back
+    let armored = ctx.select::<PlayerInteraction>(victim_id).armored();
+    let delta_vel = if armored {
+        V2::new(0.0, 0.0)
+    } else {
+        delta_vel
+    };
+
+    let max_points = 24.0;
+    let damage_multiplier = 1.0 + attacker_strength as f64 / (max_points * 2.0)
+        - (victim_defence as f64 + 1.0) / (max_points * 2.0);
+    let handicap = attacker.as_ref().map(|a| a.handicap).unwrap_or(1.0);
+    let damage = damage * damage_multiplier * handicap;
+
+    let shielded = ctx.select::<PlayerInteraction>(victim_id).shield.raised();
+    let knockback = if shielded {
+        V2::new(0.0, 0.0)
+    } else {
+        delta_vel
+    };
+    let victim_vel = ctx.select::<RigidBody>(victim_id).vel;
+    ctx.select::<Victim>(victim_id)
+        .freeze(hitlag, victim_vel, knockback);
+    if let (Some(owner), true) = (owner, attacker_hitlag) {
+        let owner_vel = ctx.select::<RigidBody>(owner).vel;
+        ctx.select::<Victim>(owner)
+            .freeze(hitlag, owner_vel, V2::new(0.0, 0.0));
+    }
+
+    if shielded {
+        hit_shield(ctx, owner, victim_id, damage, stun_time);
+        return;
+    }
+
+    let victim = ctx.select::<Victim>(victim_id);
+    if victim.stunned.is_none() {
+        victim.combo = Combo::default();
+    }
+    victim.combo.hits += 1;
+    victim.combo.damage += damage;
+    if !armored {
+        victim.stunned = stun_time;
+        victim.tumbling = tumble;
+    }
+
+    let player = ctx.select::<Player>(victim_id);
+
+    player.damage_taken += damage;
+    if let Some(stamina) = &mut player.stamina {
+        *stamina -= damage;
+    }
+
+    // numbers pop up where the hurtbox overlaps the victim
+    let color = player_damage_color(player.damage_taken);
+    let hurtbox_center = hurtbox_body.pos + hurtbox_body.size.div_comps(2.0);
+    let hit_point = V2::new(
+        clamp(
+            hurtbox_center.x,
+            victim_body.pos.x,
+            victim_body.pos.x + victim_body.size.x,
+        ),
+        clamp(
+            hurtbox_center.y,
+            victim_body.pos.y,
+            victim_body.pos.y + victim_body.size.y,
+        ),
+    );
+    spawn!(ctx, DamageNumber::new(damage, hit_point, color));
+
+    let victim_gamepad = ctx.select::<PlayerInteraction>(victim_id).gamepad.clone();
+    if let Some(gamepad) = victim_gamepad {
+        let strength = clamp(delta_vel.len() / 2000.0, 0.3, 1.0);
+        gamepad.rumble(ctx, strength, 0.25);
+    }
+    let attacker_gamepad = owner.and_then(|id| ctx.select::<PlayerInteraction>(id).gamepad.clone());
+    if let Some(gamepad) = attacker_gamepad {
+        gamepad.rumble(ctx, 0.2, 0.1);
+    }
+}
+
+/// Parries put the stun of the hit on the attacker, other hits wear
+/// the shield down and stun the victim if it breaks.
+fn hit_shield(
+    ctx: &mut Context,
+    owner: Option<engine::Id>,
+    victim_id: u64,
+    damage: f64,
+    stun_time: Option<f64>,
+) {
+    let shield = &mut ctx.select::<PlayerInteraction>(victim_id).shield;
+    if shield.parrying() {
+        if let Some(owner) = owner {
+            ctx.select::<Victim>(owner).stunned = stun_time;
+        }
+        return;
+    }
+    if shield.absorb(damage) {
+        ctx.select::<Victim>(victim_id).stunned = Some(Shield::BREAK_STUN);
+    }
+}
+
+/// Rotates knockback toward the held direction, by `max_angle` degrees
+/// when holding perpendicular to it and not at all when holding along it.
+pub fn directional_influence(knockback: V2, held: V2, max_angle: f64) -> V2 {
+    let held = if held.len() > 1.0 {
+        held.normalized()
+    } else {
+        held
+    };
+    let influence = knockback.normalized().cross(held);
+    let (sin, cos) = (max_angle * influence).to_radians().sin_cos();
+    V2::new(
+        knockback.x * cos - knockback.y * sin,
+        knockback.x * sin + knockback.y * cos,
+    )
+}
+
+#[test]
+fn test_directional_influence() {
+    let knockback = V2::new(100.0, 0.0);
+
+    let along = directional_influence(knockback, V2::new(1.0, 0.0), 15.0);
+    assert!((along - knockback).len() < 1e-9);
+
+    // holding down, +y on screen, bends rightward knockback downward
+    let down = directional_influence(knockback, V2::new(0.0, 1.0), 15.0);
+    assert!(down.y > 0.0);
+    assert!((down.len() - knockback.len()).abs() < 1e-9);
+    let angle = down.y.atan2(down.x).to_degrees();
+    assert!((angle - 15.0).abs() < 1e-9);
+
+    let up = directional_influence(knockback, V2::new(0.0, -1.0), 15.0);
+    assert!((up.y + down.y).abs() < 1e-9);
+}
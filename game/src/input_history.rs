@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+
+use crate::controls::{Action, ActionState};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InputEvent {
+    pub action: Action,
+    pub frame: u64,
+    /// Seconds since the history was created.
+    pub time: f64,
+}
+
+/// Ring buffer of the latest presses of a player, newest last. Lets game
+/// logic act on presses made slightly too early, e.g. during stun, a dodge
+/// or an attack cooldown.
+#[derive(Clone, Debug)]
+pub struct InputHistory {
+    events: VecDeque<InputEvent>,
+    capacity: usize,
+    frame: u64,
+    time: f64,
+    /// How old, in seconds, a press may be for `take_buffered` to still act on it.
+    pub buffer_window: f64,
+}
+
+impl Default for InputHistory {
+    fn default() -> Self {
+        Self::new(32, 0.15)
+    }
+}
+
+impl InputHistory {
+    pub fn new(capacity: usize, buffer_window: f64) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            frame: 0,
+            time: 0.0,
+            buffer_window,
+        }
+    }
+
+    pub fn record(&mut self, input: &ActionState, delta: f64) {
+        self.advance(delta);
+        for action in Action::ALL {
+            if input.just_pressed(action) {
+                self.record_press(action);
+            }
+        }
+    }
+
+    pub fn advance(&mut self, delta: f64) {
+        self.frame += 1;
+        self.time += delta;
+    }
+
+    pub fn record_press(&mut self, action: Action) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(InputEvent {
+            action,
+            frame: self.frame,
+            time: self.time,
+        });
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &InputEvent> {
+        self.events.iter()
+    }
+
+    fn within(&self, window: f64) -> impl DoubleEndedIterator<Item = &InputEvent> {
+        let oldest = self.time - window;
+        self.events.iter().filter(move |event| event.time >= oldest)
+    }
+
+    pub fn pressed_within(&self, action: Action, window: f64) -> bool {
+        self.within(window).any(|event| event.action == action)
+    }
+
+    /// Removes the newest press of the action within the buffer window,
+    /// so a single press is never acted on twice.
+    pub fn take_buffered(&mut self, action: Action) -> bool {
+        let oldest = self.time - self.buffer_window;
+        let Some(index) = self
+            .events
+            .iter()
+            .rposition(|event| event.action == action && event.time >= oldest)
+        else {
+            return false;
+        };
+        self.events.remove(index);
+        true
+    }
+
+    /// Whether the actions were pressed in order within the window, e.g.
+    /// `[Down, Right, LightAttack]`. Other presses in between are allowed.
+    pub fn motion_performed(&self, motion: &[Action], window: f64) -> bool {
+        let mut remaining = motion.iter().rev().peekable();
+        for event in self.within(window).rev() {
+            if remaining
+                .next_if(|action| **action == event.action)
+                .is_some()
+                && remaining.peek().is_none()
+            {
+                return true;
+            }
+        }
+        motion.is_empty()
+    }
+}
+
+#[test]
+fn test_input_history_buffer() {
+    let mut history = InputHistory::new(4, 0.15);
+    history.advance(0.1);
+    history.record_press(Action::LightAttack);
+    history.advance(0.1);
+
+    assert!(history.pressed_within(Action::LightAttack, 0.15));
+    assert!(!history.pressed_within(Action::LightAttack, 0.05));
+    assert!(history.take_buffered(Action::LightAttack));
+    assert!(!history.take_buffered(Action::LightAttack));
+
+    history.record_press(Action::LightAttack);
+    history.advance(0.2);
+    assert!(!history.take_buffered(Action::LightAttack));
+
+    for _ in 0..5 {
+        history.record_press(Action::Up);
+    }
+    assert_eq!(history.events().count(), 4);
+}
+
+#[test]
+fn test_input_history_motion() {
+    use Action::*;
+
+    let mut history = InputHistory::default();
+    for action in [Down, Dodge, Right, LightAttack] {
+        history.advance(1.0 / 144.0);
+        history.record_press(action);
+    }
+
+    assert!(history.motion_performed(&[Down, Right, LightAttack], 0.1));
+    assert!(!history.motion_performed(&[Right, Down, LightAttack], 0.1));
+    assert!(!history.motion_performed(&[Down, Right, LightAttack], 0.01));
+}
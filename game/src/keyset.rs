@@ -1,5 +1,8 @@
-use engine::Keycode;
+use engine::{ControllerAxis, ControllerButton, Keycode};
 
+use crate::controls::{Action, AxisDirection, Binding, Bindings};
+
+/// Default control profiles, every player can rebind them in the controls menu.
 #[derive(Clone)]
 pub enum Keyset {
     Wasd,
@@ -7,41 +10,60 @@ pub enum Keyset {
 }
 
 impl Keyset {
-    pub fn right(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::D,
-            Keyset::ArrowKeys => Keycode::Right,
-        }
-    }
-    pub fn left(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::A,
-            Keyset::ArrowKeys => Keycode::Left,
-        }
-    }
-    pub fn up(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::W,
-            Keyset::ArrowKeys => Keycode::Up,
-        }
-    }
-    pub fn down(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::S,
-            Keyset::ArrowKeys => Keycode::Down,
-        }
-    }
-    pub fn light_attack(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::J,
-            Keyset::ArrowKeys => Keycode::KpEnter,
-        }
-    }
-
-    pub fn dodge(&self) -> Keycode {
-        match self {
-            Keyset::Wasd => Keycode::K,
-            Keyset::ArrowKeys => Keycode::KpPeriod,
-        }
+    pub fn bindings(&self) -> Bindings {
+        use Action::*;
+        let keys = match self {
+            Keyset::Wasd => Bindings::new()
+                .bind(Right, Binding::Key(Keycode::D))
+                .bind(Left, Binding::Key(Keycode::A))
+                .bind(Up, Binding::Key(Keycode::W))
+                .bind(Down, Binding::Key(Keycode::S))
+                .bind(LightAttack, Binding::Key(Keycode::J))
+                .bind(HeavyAttack, Binding::Key(Keycode::L))
+                .bind(Special, Binding::Key(Keycode::I))
+                .bind(Shield, Binding::Key(Keycode::U))
+                .bind(Grab, Binding::Key(Keycode::O))
+                .bind(Dodge, Binding::Key(Keycode::K)),
+            Keyset::ArrowKeys => Bindings::new()
+                .bind(Right, Binding::Key(Keycode::Right))
+                .bind(Left, Binding::Key(Keycode::Left))
+                .bind(Up, Binding::Key(Keycode::Up))
+                .bind(Down, Binding::Key(Keycode::Down))
+                .bind(LightAttack, Binding::Key(Keycode::KpEnter))
+                .bind(HeavyAttack, Binding::Key(Keycode::Kp0))
+                .bind(Special, Binding::Key(Keycode::Kp1))
+                .bind(Shield, Binding::Key(Keycode::Kp2))
+                .bind(Grab, Binding::Key(Keycode::Kp3))
+                .bind(Dodge, Binding::Key(Keycode::KpPeriod)),
+        };
+        keys.bind(Right, Binding::Button(ControllerButton::DPadRight))
+            .bind(
+                Right,
+                Binding::Axis(ControllerAxis::LeftX, AxisDirection::Positive),
+            )
+            .bind(Left, Binding::Button(ControllerButton::DPadLeft))
+            .bind(
+                Left,
+                Binding::Axis(ControllerAxis::LeftX, AxisDirection::Negative),
+            )
+            .bind(Up, Binding::Button(ControllerButton::A))
+            .bind(Up, Binding::Button(ControllerButton::Y))
+            .bind(Down, Binding::Button(ControllerButton::DPadDown))
+            .bind(
+                Down,
+                Binding::Axis(ControllerAxis::LeftY, AxisDirection::Positive),
+            )
+            .bind(LightAttack, Binding::Button(ControllerButton::X))
+            .bind(
+                HeavyAttack,
+                Binding::Axis(ControllerAxis::TriggerRight, AxisDirection::Positive),
+            )
+            .bind(Special, Binding::Button(ControllerButton::LeftShoulder))
+            .bind(
+                Shield,
+                Binding::Axis(ControllerAxis::TriggerLeft, AxisDirection::Positive),
+            )
+            .bind(Dodge, Binding::Button(ControllerButton::B))
+            .bind(Grab, Binding::Button(ControllerButton::RightShoulder))
     }
 }
@@ -1,10 +1,14 @@
 use engine::{
-    clamp, query, rigid_body::RigidBody, spawn, Component, Context, Error, System, Texture, V2,
+    clamp, collision::Trigger, query, rigid_body::RigidBody, spawn, Component, Context, Error,
+    System, Texture, V2,
 };
 use shared::Hero;
 
 use crate::{
+    grab::GrabState,
     hud::{ReturnToMenu, TrashTalk},
+    hurtbox::Hurtbox,
+    match_rules::{time_up_loser, MatchRules, MatchState},
     player::Player,
     player_interaction::PlayerInteraction,
     server::Server,
@@ -13,52 +17,132 @@ use crate::{
     timer::Timer,
 };
 
+/// Players leaving this trigger are knocked off.
+#[derive(Component, Clone)]
+pub struct BlastZone;
+
 pub struct KnockoffSystem(pub u64);
 impl System for KnockoffSystem {
-    fn on_update(&self, ctx: &mut Context, _delta: f64) -> Result<(), Error> {
-        let max_offset_from_screen = 200.0;
-        for id in query!(ctx, PlayerInteraction, RigidBody, Player).clone() {
-            let rigid_body = ctx.select::<RigidBody>(id).clone();
-            if body_outside_area(&rigid_body, max_offset_from_screen) {
-                let loser_id = id;
-                let player = ctx.select::<Player>(loser_id);
-                if player.is_alive() {
-                    player.damage_taken = 0.0;
-                    player.lives -= 1;
-                    let player_pos = rigid_body.pos;
-                    let player_size = rigid_body.size;
-                    spawn_death_animation(ctx, player_pos, player_size);
-                    let sound_player = ctx.select_one::<SoundPlayer>();
-                    sound_player.set_effect_volume(0.5);
-                    sound_player.play_effect("assets/sounds/explosion.ogg");
-                };
-                let player = ctx.select::<Player>(loser_id);
-                let player_is_dead = player.is_dead();
-                if player_is_dead {
-                    let loser_hero = player.hero.clone();
-                    let loser_hero_kind = loser_hero.kind.clone();
-                    ctx.despawn(loser_id);
-                    let winner = ctx.select_one::<Player>().clone();
-                    let winner_hero_kind = winner.hero.kind.clone();
-                    spawn!(ctx, TrashTalk::new(winner_hero_kind, loser_hero_kind));
-                    spawn!(ctx, ReturnToMenu::new());
-                    send_match_result(ctx, &winner.hero, &loser_hero);
+    fn on_update(&self, ctx: &mut Context, delta: f64) -> Result<(), Error> {
+        // the match is over once the loser is despawned
+        if query!(ctx, Player).len() < 2 {
+            return Ok(());
+        }
+        let rules = ctx.clone_one::<MatchRules>();
+        let players = query!(ctx, PlayerInteraction, RigidBody, Player);
+        for zone_id in query!(ctx, BlastZone, Trigger) {
+            let exited = ctx.select::<Trigger>(zone_id).exited().collect::<Vec<_>>();
+            for id in exited {
+                if !players.contains(&id) {
+                    continue;
+                }
+                // in stamina mode only running out of stamina loses a stock
+                if rules.stamina.is_some() {
+                    respawn(ctx, id);
                     continue;
                 }
-                let rigid_body = ctx.select::<RigidBody>(loser_id);
-                rigid_body.pos = V2::new((1280.0 - rigid_body.size.x) / 2.0, 100.0);
-                rigid_body.vel = V2::new(0.0, 0.0);
+                let rigid_body = ctx.select::<RigidBody>(id).clone();
+                spawn_death_animation(ctx, rigid_body.pos, rigid_body.size);
+                if lose_stock(ctx, id) {
+                    return Ok(());
+                }
+            }
+        }
+        for id in players {
+            let out_of_stamina = ctx
+                .select::<Player>(id)
+                .stamina
+                .is_some_and(|stamina| stamina <= 0.0);
+            if out_of_stamina && lose_stock(ctx, id) {
+                return Ok(());
             }
         }
+        self.update_clock(ctx, delta);
         Ok(())
     }
 }
 
-fn body_outside_area(rigid_body: &RigidBody, max_offset_from_screen: f64) -> bool {
-    rigid_body.pos.x + rigid_body.size.x < -max_offset_from_screen
-        || rigid_body.pos.x > 1280.0 + max_offset_from_screen
-        || rigid_body.pos.y + rigid_body.size.y < -max_offset_from_screen
-        || rigid_body.pos.y > 720.0 + max_offset_from_screen
+impl KnockoffSystem {
+    /// Ends the match by stocks left when the time runs out, or goes to
+    /// sudden death on a tie.
+    fn update_clock(&self, ctx: &mut Context, delta: f64) {
+        let state = ctx.select_one::<MatchState>();
+        let Some(timer) = &mut state.timer else {
+            return;
+        };
+        timer.update(delta);
+        if !timer.done() {
+            return;
+        }
+        state.timer = None;
+
+        let lives = query!(ctx, Player)
+            .into_iter()
+            .map(|id| (id, ctx.select::<Player>(id).lives))
+            .collect::<Vec<_>>();
+        if let Some(loser) = time_up_loser(&lives) {
+            end_match(ctx, loser);
+            return;
+        }
+
+        ctx.select_one::<MatchState>().sudden_death = true;
+        let sudden_death_damage = 300.0;
+        for id in query!(ctx, Player) {
+            let player = ctx.select::<Player>(id);
+            player.lives = 1;
+            player.damage_taken = sudden_death_damage;
+            if let Some(stamina) = &mut player.stamina {
+                *stamina = 1.0;
+            }
+        }
+    }
+}
+
+/// Takes a stock from the player and returns true if it was their last,
+/// ending the match.
+fn lose_stock(ctx: &mut Context, id: u64) -> bool {
+    let stamina = ctx.clone_one::<MatchRules>().stamina;
+    let player = ctx.select::<Player>(id);
+    if player.is_alive() {
+        player.damage_taken = 0.0;
+        player.stamina = stamina;
+        player.lives -= 1;
+        let sound_player = ctx.select_one::<SoundPlayer>();
+        sound_player.set_effect_volume(0.5);
+        sound_player.play_effect("assets/sounds/explosion.ogg");
+    };
+    if ctx.select::<Player>(id).is_dead() {
+        end_match(ctx, id);
+        return true;
+    }
+    respawn(ctx, id);
+    false
+}
+
+fn respawn(ctx: &mut Context, id: u64) {
+    let rigid_body = ctx.select::<RigidBody>(id);
+    rigid_body.pos = V2::new((1280.0 - rigid_body.size.x) / 2.0, 100.0);
+    rigid_body.vel = V2::new(0.0, 0.0);
+}
+
+fn end_match(ctx: &mut Context, loser_id: u64) {
+    let loser_hero = ctx.select::<Player>(loser_id).hero.clone();
+    let loser_hero_kind = loser_hero.kind.clone();
+    ctx.despawn(loser_id);
+    // nothing may refer to the loser once it's gone
+    for hurtbox_id in query!(ctx, Hurtbox) {
+        if ctx.select::<Hurtbox>(hurtbox_id).owner == Some(loser_id) {
+            ctx.despawn(hurtbox_id);
+        }
+    }
+    for id in query!(ctx, PlayerInteraction) {
+        ctx.select::<PlayerInteraction>(id).grab_state = GrabState::Free;
+    }
+    let winner = ctx.select_one::<Player>().clone();
+    let winner_hero_kind = winner.hero.kind.clone();
+    spawn!(ctx, TrashTalk::new(winner_hero_kind, loser_hero_kind));
+    spawn!(ctx, ReturnToMenu::new());
+    send_match_result(ctx, &winner.hero, &loser_hero);
 }
 
 fn send_match_result(ctx: &mut Context, winner: &Hero, loser: &Hero) {
@@ -0,0 +1,162 @@
+use engine::{
+    collision::{Trigger, DEFAULT_LAYER},
+    rigid_body::RigidBody,
+    spawn, Component, Context, Id, V2,
+};
+
+use crate::timer::Timer;
+
+/// Trigger layer of ledge grab zones, only overlapping solid colliders.
+pub const LEDGE_LAYER: u32 = 1 << 3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LedgeSide {
+    Left,
+    Right,
+}
+
+/// Grab zone at a top corner of a stage collider.
+#[derive(Component, Clone)]
+pub struct Ledge {
+    pub corner: V2,
+    pub side: LedgeSide,
+}
+
+impl Ledge {
+    /// Beside the stage, with the top of the body just above the corner.
+    pub fn hang_pos(&self, size: V2) -> V2 {
+        let y = self.corner.y - size.y / 4.0;
+        match self.side {
+            LedgeSide::Left => V2::new(self.corner.x - size.x, y),
+            LedgeSide::Right => V2::new(self.corner.x, y),
+        }
+    }
+
+    /// Standing on the stage at the corner.
+    pub fn getup_pos(&self, size: V2) -> V2 {
+        let y = self.corner.y - size.y;
+        match self.side {
+            LedgeSide::Left => V2::new(self.corner.x, y),
+            LedgeSide::Right => V2::new(self.corner.x - size.x, y),
+        }
+    }
+
+    /// Horizontal direction from the ledge onto the stage.
+    pub fn inward(&self) -> f64 {
+        match self.side {
+            LedgeSide::Left => 1.0,
+            LedgeSide::Right => -1.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum LedgeState {
+    Free,
+    Hanging {
+        ledge: Ledge,
+        timer: Timer,
+    },
+    /// Can't grab a ledge again until the timer is done.
+    Released(Timer),
+}
+
+impl LedgeState {
+    pub fn update(&mut self, delta: f64) {
+        match self {
+            LedgeState::Hanging { timer, .. } => {
+                timer.update(delta);
+                if timer.done() {
+                    *self = LedgeState::released();
+                }
+            }
+            LedgeState::Released(timer) => {
+                timer.update(delta);
+                if timer.done() {
+                    *self = LedgeState::Free;
+                }
+            }
+            LedgeState::Free => (),
+        }
+    }
+
+    pub fn hanging(ledge: Ledge) -> Self {
+        LedgeState::Hanging {
+            ledge,
+            timer: Timer::new(3.0),
+        }
+    }
+
+    pub fn released() -> Self {
+        LedgeState::Released(Timer::new(0.5))
+    }
+
+    /// Keeps the body hanging from the ledge. Hits make the player let go
+    /// without touching the body, so their knockback is kept. Returns
+    /// whether the player is still hanging.
+    pub fn hold(&mut self, body: &mut RigidBody, hit: bool) -> bool {
+        let LedgeState::Hanging { ledge, .. } = self else {
+            return false;
+        };
+        if hit {
+            *self = LedgeState::released();
+            return false;
+        }
+        body.pos = ledge.hang_pos(body.size);
+        body.vel = V2::new(0.0, 0.0);
+        true
+    }
+
+    /// Only the first part of a hang is invulnerable, so ledges can't be
+    /// held forever.
+    pub fn invulnerable(&self) -> bool {
+        match self {
+            LedgeState::Hanging { timer, .. } => timer.time_passed() < 1.0,
+            LedgeState::Free | LedgeState::Released(_) => false,
+        }
+    }
+}
+
+/// Ledges on both top corners of a stage collider.
+pub fn spawn_ledges(ctx: &mut Context, pos: V2, size: V2) -> [Id; 2] {
+    let zone_size = V2::new(48.0, 32.0);
+    let left = Ledge {
+        corner: pos,
+        side: LedgeSide::Left,
+    };
+    let right = Ledge {
+        corner: pos.add_x(size.x),
+        side: LedgeSide::Right,
+    };
+    [left, right].map(|ledge| {
+        let zone_pos = match ledge.side {
+            LedgeSide::Left => ledge.corner - V2::new(zone_size.x - 8.0, 8.0),
+            LedgeSide::Right => ledge.corner - V2::new(8.0, 8.0),
+        };
+        spawn!(
+            ctx,
+            RigidBody::new().with_pos(zone_pos).with_size(zone_size),
+            Trigger::new().layer(LEDGE_LAYER).mask(DEFAULT_LAYER),
+            ledge,
+        )
+    })
+}
+
+#[test]
+fn test_hit_lets_go_of_ledge_with_knockback() {
+    let ledge = Ledge {
+        corner: V2::new(100.0, 300.0),
+        side: LedgeSide::Left,
+    };
+    let size = V2::new(40.0, 40.0);
+    let mut body = RigidBody::new().with_size(size);
+    let mut state = LedgeState::hanging(ledge.clone());
+    assert!(state.hold(&mut body, false));
+    assert_eq!(body.pos.x, ledge.hang_pos(size).x);
+
+    let knockback = V2::new(-500.0, -300.0);
+    body.vel = knockback;
+    assert!(!state.hold(&mut body, true));
+    assert!(matches!(state, LedgeState::Released(_)));
+    assert_eq!((body.vel.x, body.vel.y), (knockback.x, knockback.y));
+}
@@ -1,3 +1,4 @@
+use crate::controls_menu::ControlsMenuSystem;
 use crate::hero_creator::HeroCreatorSystem;
 use crate::sound_player::SoundPlayer;
 use crate::start_game::StartGameSystem;
@@ -19,6 +20,7 @@ pub struct MainMenu {
 pub enum Node {
     StartGame,
     HeroCreator,
+    Controls,
     Exit,
 }
 
@@ -32,6 +34,7 @@ impl From<Node> for ui::NodeId {
 pub enum Event {
     StartGame,
     HeroCreator,
+    Controls,
     Exit,
 }
 
@@ -79,6 +82,14 @@ impl System for MainMenuSystem {
                         .border_thickness(2)
                         .id(Node::HeroCreator)
                         .on_click(Event::HeroCreator),
+                    Button("Controls")
+                        .width(200)
+                        .color((255, 255, 255))
+                        .background_color((50, 50, 50))
+                        .padding(15)
+                        .border_thickness(2)
+                        .id(Node::Controls)
+                        .on_click(Event::Controls),
                     Button("Exit")
                         .width(200)
                         .color((255, 255, 255))
@@ -106,6 +117,11 @@ impl System for MainMenuSystem {
             ctx.add_system(HeroCreatorSystem);
         });
 
+        dom.add_event_handler(Event::Controls, move |_dom, ctx, _node_id| {
+            ctx.remove_system(system_id);
+            ctx.add_system(ControlsMenuSystem);
+        });
+
         dom.add_event_handler(Event::Exit, |_dom, ctx, _node_id| {
             ctx.quit();
         });
@@ -121,6 +137,7 @@ impl System for MainMenuSystem {
                 focus: SharedPtr::new(ui::focus::Focus::new([
                     Node::StartGame,
                     Node::HeroCreator,
+                    Node::Controls,
                     Node::Exit
                 ])),
             }
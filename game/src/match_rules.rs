@@ -0,0 +1,160 @@
+use engine::{Component, Id};
+
+use crate::{player::PlayerKind, timer::Timer};
+
+/// Chosen on the start game screen, read when the game starts.
+#[derive(Component, Clone, Debug)]
+pub struct MatchRules {
+    pub stocks: i8,
+    /// Seconds before the match ends on time, no limit when not set.
+    pub time_limit: Option<f64>,
+    /// Hit points of every stock in stamina mode, where stocks are lost by
+    /// running out of them instead of by leaving the blast zone.
+    pub stamina: Option<f64>,
+    /// Multiplier on the damage dealt by the hero on each side.
+    pub handicap_left: f64,
+    pub handicap_right: f64,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            stocks: 3,
+            time_limit: None,
+            stamina: None,
+            handicap_left: 1.0,
+            handicap_right: 1.0,
+        }
+    }
+}
+
+impl MatchRules {
+    const MAX_STOCKS: i8 = 5;
+    const TIME_LIMITS: [Option<f64>; 4] = [None, Some(120.0), Some(180.0), Some(300.0)];
+    const STAMINA: f64 = 150.0;
+    const HANDICAPS: [f64; 5] = [1.0, 1.25, 1.5, 0.5, 0.75];
+
+    pub fn next_stocks(&mut self) {
+        self.stocks = self.stocks % Self::MAX_STOCKS + 1;
+    }
+
+    pub fn next_time_limit(&mut self) {
+        let limits = Self::TIME_LIMITS;
+        let index = limits
+            .iter()
+            .position(|limit| *limit == self.time_limit)
+            .unwrap_or(0);
+        self.time_limit = limits[(index + 1) % limits.len()];
+    }
+
+    pub fn next_mode(&mut self) {
+        self.stamina = match self.stamina {
+            Some(_) => None,
+            None => Some(Self::STAMINA),
+        };
+    }
+
+    pub fn next_handicap(&mut self, kind: &PlayerKind) {
+        let handicaps = Self::HANDICAPS;
+        let handicap = match kind {
+            PlayerKind::Left => &mut self.handicap_left,
+            PlayerKind::Right => &mut self.handicap_right,
+        };
+        let index = handicaps
+            .iter()
+            .position(|value| value == handicap)
+            .unwrap_or(0);
+        *handicap = handicaps[(index + 1) % handicaps.len()];
+    }
+
+    pub fn handicap(&self, kind: &PlayerKind) -> f64 {
+        match kind {
+            PlayerKind::Left => self.handicap_left,
+            PlayerKind::Right => self.handicap_right,
+        }
+    }
+
+    pub fn stocks_label(&self) -> String {
+        format!("Stocks: {}", self.stocks)
+    }
+
+    pub fn time_limit_label(&self) -> String {
+        match self.time_limit {
+            Some(limit) => format!("Time: {}", clock_text(limit)),
+            None => "Time: off".to_string(),
+        }
+    }
+
+    pub fn mode_label(&self) -> String {
+        match self.stamina {
+            Some(stamina) => format!("Mode: Stamina {stamina:.0}"),
+            None => "Mode: Percent".to_string(),
+        }
+    }
+
+    pub fn handicap_label(&self, kind: &PlayerKind) -> String {
+        format!("Handicap: {:.0}%", self.handicap(kind) * 100.0)
+    }
+}
+
+/// Clock of the running match, spawned with the game.
+#[derive(Component, Clone)]
+pub struct MatchState {
+    pub timer: Option<Timer>,
+    /// Set when the time ran out on a tie, the next stock lost ends it.
+    pub sudden_death: bool,
+}
+
+impl MatchState {
+    pub fn new(rules: &MatchRules) -> Self {
+        Self {
+            timer: rules.time_limit.map(Timer::new),
+            sudden_death: false,
+        }
+    }
+}
+
+/// Minutes and seconds, rounding up so the clock shows 0:00 only at the end.
+pub fn clock_text(seconds: f64) -> String {
+    let seconds = seconds.max(0.0).ceil() as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The player with the fewest stocks when the time runs out, `None` on a tie.
+pub fn time_up_loser(lives: &[(Id, i8)]) -> Option<Id> {
+    let fewest = lives.iter().map(|(_, lives)| *lives).min()?;
+    let mut losers = lives.iter().filter(|(_, lives)| *lives == fewest);
+    let (loser, _) = losers.next()?;
+    if losers.next().is_some() {
+        return None;
+    }
+    Some(*loser)
+}
+
+#[test]
+fn test_rules_cycle() {
+    let mut rules = MatchRules {
+        stocks: MatchRules::MAX_STOCKS,
+        ..Default::default()
+    };
+    rules.next_stocks();
+    assert_eq!(rules.stocks, 1);
+
+    rules.next_time_limit();
+    assert_eq!(rules.time_limit_label(), "Time: 2:00");
+    for _ in 1..MatchRules::TIME_LIMITS.len() {
+        rules.next_time_limit();
+    }
+    assert_eq!(rules.time_limit, None);
+
+    rules.next_handicap(&PlayerKind::Right);
+    assert_eq!(rules.handicap(&PlayerKind::Right), 1.25);
+    assert_eq!(rules.handicap(&PlayerKind::Left), 1.0);
+}
+
+#[test]
+fn test_time_up_loser() {
+    assert_eq!(time_up_loser(&[(1, 2), (2, 1)]), Some(2));
+    assert_eq!(time_up_loser(&[(1, 2), (2, 2)]), None);
+    assert_eq!(clock_text(59.2), "1:00");
+}
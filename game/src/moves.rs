@@ -0,0 +1,395 @@
+use std::{collections::HashMap, rc::Rc};
+
+use engine::{clamp, physics::Rect, rigid_body::RigidBody, Component, V2};
+use serde::Deserialize;
+use shared::HeroKind;
+
+use crate::attacks::AttackKind;
+
+/// Where a move's hurtbox is placed around the attacker.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Anchor {
+    Above,
+    /// Centered on the attacker's feet.
+    Below,
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct HurtboxRect {
+    pub offset: (f64, f64),
+    pub size: (f64, f64),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Frame {
+    pub texture: String,
+    /// Part of the move that hurts during the frame, all of it if not set.
+    #[serde(default)]
+    pub hurtbox: Option<HurtboxRect>,
+}
+
+/// Knockback speed is `power`, the hurtbox speed and terms scaling with
+/// the victim's damage taken, the attacker's strength and the victim's
+/// defence.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Knockback {
+    /// Degrees counter-clockwise from the right.
+    pub angle: f64,
+    /// Part of the speed pushing the victim away from the hurtbox, across
+    /// the angle.
+    #[serde(default)]
+    pub aim: f64,
+    pub power: f64,
+    #[serde(default)]
+    pub per_damage_taken: f64,
+    #[serde(default)]
+    pub per_damage_taken_squared: f64,
+    #[serde(default)]
+    pub per_strength: f64,
+    #[serde(default)]
+    pub per_defence: f64,
+}
+
+impl Knockback {
+    pub fn direction(&self) -> V2 {
+        let angle = self.angle.to_radians();
+        V2::new(angle.cos(), -angle.sin())
+    }
+
+    /// Same angle, with every speed term multiplied.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            power: self.power * factor,
+            per_damage_taken: self.per_damage_taken * factor,
+            per_damage_taken_squared: self.per_damage_taken_squared * factor,
+            per_strength: self.per_strength * factor,
+            per_defence: self.per_defence * factor,
+            ..self.clone()
+        }
+    }
+}
+
+/// Holding the attack scales damage and knockback, reaching `max` after
+/// `time` seconds, when the attack is released by itself.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Charge {
+    pub time: f64,
+    pub max: f64,
+}
+
+/// Hurtbox flying on its own for the move's duration, instead of staying
+/// next to the attacker.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Projectile {
+    pub vel: (f64, f64),
+    #[serde(default)]
+    pub gravity_scale: f64,
+    /// Distance it flies before despawning, only its duration if not set.
+    #[serde(default)]
+    pub range: Option<f64>,
+    /// Victims it passes through, it despawns when hitting one more.
+    #[serde(default)]
+    pub pierce: u32,
+    /// Whether an attack hitting it sends it back.
+    #[serde(default)]
+    pub reflectable: bool,
+}
+
+/// Seconds before the next attack, `max` without agility and `min` with
+/// full agility.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Cooldown {
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Sound {
+    pub path: String,
+    pub volume: f64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Move {
+    pub anchor: Anchor,
+    pub size: (f64, f64),
+    /// Part of the attacker's velocity the hurtbox moves with.
+    #[serde(default)]
+    pub inherit_velocity: f64,
+    /// Whether the hurtbox stays attached to the attacker instead.
+    #[serde(default)]
+    pub follow: bool,
+    /// Velocity given to the attacker when the move starts.
+    #[serde(default)]
+    pub attacker_vel: Option<(f64, f64)>,
+    /// The attacker takes damage but no knockback or stun during the move.
+    #[serde(default)]
+    pub armor: bool,
+    /// Hits on the attacker during the move are countered with the move
+    /// instead. Counters usually have a startup as long as the duration,
+    /// so they only hit by countering.
+    #[serde(default)]
+    pub counter: bool,
+    #[serde(default)]
+    pub projectile: Option<Projectile>,
+    /// Seconds the hurtbox lasts, split evenly between the frames.
+    pub duration: f64,
+    /// Seconds at the start of the duration before the hurtbox hurts.
+    #[serde(default)]
+    pub startup: f64,
+    pub frames: Vec<Frame>,
+    pub damage: f64,
+    pub knockback: Knockback,
+    pub stun: f64,
+    /// Stun per knockback speed, used when longer than `stun`.
+    #[serde(default)]
+    pub stun_per_velocity: f64,
+    pub cooldown: Cooldown,
+    #[serde(default)]
+    pub charge: Option<Charge>,
+    /// Seconds the attacker can't act when landing during an aerial.
+    #[serde(default)]
+    pub landing_lag: f64,
+    /// Parts of an aerial, in seconds since it started, where landing has
+    /// no lag.
+    #[serde(default)]
+    pub auto_cancel: Vec<(f64, f64)>,
+    #[serde(default)]
+    pub sound: Option<Sound>,
+    /// Set on moves mirrored to face left.
+    #[serde(skip)]
+    pub flipped: bool,
+}
+
+impl Move {
+    pub fn size(&self) -> V2 {
+        self.size.into()
+    }
+
+    pub fn pos(&self, body: &RigidBody) -> V2 {
+        let size = self.size();
+        match self.anchor {
+            Anchor::Above => V2::new(
+                body.pos.x + (body.size.x - size.x) / 2.0,
+                body.pos.y - size.y,
+            ),
+            Anchor::Below => V2::new(
+                body.pos.x + (body.size.x - size.x) / 2.0,
+                body.pos.y + body.size.y - size.y,
+            ),
+            Anchor::Left => V2::new(
+                body.pos.x - size.x,
+                body.pos.y + (body.size.y - size.y) / 2.0,
+            ),
+            Anchor::Right => V2::new(
+                body.pos.x + body.size.x,
+                body.pos.y + (body.size.y - size.y) / 2.0,
+            ),
+            Anchor::Center => body.pos + (body.size - size).div_comps(2.0),
+        }
+    }
+
+    /// The move facing the other way, for moves written facing right.
+    pub fn mirrored(&self) -> Self {
+        let anchor = match self.anchor {
+            Anchor::Left => Anchor::Right,
+            Anchor::Right => Anchor::Left,
+            anchor => anchor,
+        };
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| Frame {
+                hurtbox: frame.hurtbox.as_ref().map(|rect| HurtboxRect {
+                    offset: (self.size.0 - rect.offset.0 - rect.size.0, rect.offset.1),
+                    size: rect.size,
+                }),
+                ..frame.clone()
+            })
+            .collect();
+        Self {
+            anchor,
+            frames,
+            attacker_vel: self.attacker_vel.map(|(x, y)| (-x, y)),
+            projectile: self.projectile.as_ref().map(|projectile| Projectile {
+                vel: (-projectile.vel.0, projectile.vel.1),
+                ..projectile.clone()
+            }),
+            knockback: Knockback {
+                angle: 180.0 - self.knockback.angle,
+                ..self.knockback.clone()
+            },
+            flipped: !self.flipped,
+            ..self.clone()
+        }
+    }
+
+    /// Lag when landing `time` seconds into the move.
+    pub fn landing_lag(&self, time: f64) -> f64 {
+        let auto_cancel = self
+            .auto_cancel
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&time));
+        if auto_cancel {
+            0.0
+        } else {
+            self.landing_lag
+        }
+    }
+
+    pub fn vel(&self, body: &RigidBody) -> V2 {
+        let vel = body.vel.extend(self.inherit_velocity);
+        match &self.projectile {
+            Some(projectile) => vel + projectile.vel.into(),
+            None => vel,
+        }
+    }
+
+    pub fn frame_hurtboxes(&self) -> Vec<Option<Rect>> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let rect = frame.hurtbox.as_ref()?;
+                Some(Rect::new(rect.offset.into(), rect.size.into()))
+            })
+            .collect()
+    }
+
+    /// Seconds it takes to fully charge, none for moves without a charge.
+    pub fn charge_time(&self) -> f64 {
+        self.charge
+            .as_ref()
+            .map(|charge| charge.time)
+            .unwrap_or(0.0)
+    }
+
+    /// The move after charging it for `time` seconds.
+    pub fn charged(&self, time: f64) -> Self {
+        let Some(charge) = &self.charge else {
+            return self.clone();
+        };
+        let progress = clamp(time / charge.time, 0.0, 1.0);
+        let factor = 1.0 + (charge.max - 1.0) * progress;
+        Self {
+            damage: self.damage * factor,
+            knockback: self.knockback.scaled(factor),
+            ..self.clone()
+        }
+    }
+
+    pub fn cooldown(&self, agility: i64) -> f64 {
+        let Cooldown { min, max } = self.cooldown;
+        (max - min) * (1.0 - agility as f64 / 24.0) + min
+    }
+}
+
+/// Moves read from `assets/moves`, one file per attack kind. Heroes can
+/// override any of them in their own directory, e.g. `assets/moves/speed`,
+/// and each hero has its own special there. Loaded when a match starts, so
+/// they can be tuned between matches.
+#[derive(Component, Clone)]
+pub struct MoveSet {
+    moves: HashMap<AttackKind, Rc<Move>>,
+    hero_moves: HashMap<(HeroKind, AttackKind), Rc<Move>>,
+}
+
+impl MoveSet {
+    const DIR: &'static str = "assets/moves";
+
+    pub fn load() -> Result<Self, String> {
+        let mut moves = HashMap::new();
+        for kind in AttackKind::ALL {
+            if kind == AttackKind::Special {
+                continue;
+            }
+            let path = format!("{}/{}.json", Self::DIR, kind.name());
+            let attack = Self::read(&path)?.ok_or_else(|| format!("{path} is missing"))?;
+            moves.insert(kind, Rc::new(attack));
+        }
+
+        let mut hero_moves = HashMap::new();
+        for hero in HeroKind::ALL {
+            let dir = format!("{}/{}", Self::DIR, hero.to_string().to_lowercase());
+            for kind in AttackKind::ALL {
+                let path = format!("{dir}/{}.json", kind.name());
+                match Self::read(&path)? {
+                    Some(attack) => {
+                        hero_moves.insert((hero.clone(), kind), Rc::new(attack));
+                    }
+                    None if kind == AttackKind::Special => {
+                        return Err(format!("{path} is missing"));
+                    }
+                    None => (),
+                }
+            }
+        }
+        Ok(Self { moves, hero_moves })
+    }
+
+    fn read(path: &str) -> Result<Option<Move>, String> {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(format!("unable to read {path}: {err}")),
+        };
+        let attack: Move =
+            serde_json::from_str(&json).map_err(|err| format!("unable to parse {path}: {err}"))?;
+        if attack.frames.is_empty() {
+            return Err(format!("{path} has no frames"));
+        }
+        if attack.duration <= 0.0 {
+            return Err(format!("{path} has no duration"));
+        }
+        if attack
+            .charge
+            .as_ref()
+            .is_some_and(|charge| charge.time <= 0.0)
+        {
+            return Err(format!("{path} has no charge time"));
+        }
+        Ok(Some(attack))
+    }
+
+    pub fn get(&self, hero: &HeroKind, kind: AttackKind) -> Rc<Move> {
+        self.hero_moves
+            .get(&(hero.clone(), kind))
+            .unwrap_or_else(|| &self.moves[&kind])
+            .clone()
+    }
+}
+
+#[test]
+fn test_move_files() {
+    let moves = MoveSet::load().unwrap();
+    for (hero, kind) in HeroKind::ALL
+        .into_iter()
+        .flat_map(|hero| AttackKind::ALL.map(|kind| (hero.clone(), kind)))
+    {
+        let attack = moves.get(&hero, kind);
+        for frame in &attack.frames {
+            let path = std::path::Path::new(&frame.texture);
+            assert!(path.exists(), "{} is missing", frame.texture);
+        }
+        if let Some(sound) = &attack.sound {
+            assert!(std::path::Path::new(&sound.path).exists());
+        }
+    }
+}
+
+#[test]
+fn test_aerial_mirroring_and_landing_lag() {
+    let back_air = MoveSet::load()
+        .unwrap()
+        .get(&HeroKind::Centrist, AttackKind::BackAir);
+    let mirrored = back_air.mirrored();
+    assert!(matches!(mirrored.anchor, Anchor::Right));
+    assert!(mirrored.flipped);
+    assert!((mirrored.knockback.angle - (180.0 - back_air.knockback.angle)).abs() < 1e-9);
+    assert!(!mirrored.mirrored().flipped);
+
+    assert_eq!(back_air.landing_lag(0.0), 0.0);
+    assert_eq!(back_air.landing_lag(0.2), back_air.landing_lag);
+}
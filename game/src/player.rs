@@ -12,6 +12,10 @@ pub struct Player {
     pub hero: shared::Hero,
     pub damage_taken: f64,
     pub lives: i8,
+    /// Hit points left of the current stock in stamina mode.
+    pub stamina: Option<f64>,
+    /// Multiplier on the damage the player deals.
+    pub handicap: f64,
 }
 
 impl Player {
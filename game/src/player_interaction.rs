@@ -1,26 +1,93 @@
-use engine::{query, rigid_body::RigidBody, spawn, Component, SolidCollider, System, V2};
+use std::rc::Rc;
+
+use engine::{
+    collision::{Trigger, DEFAULT_LAYER},
+    max, min,
+    physics::OctoDirection,
+    query,
+    raycast::{self, QueryFilter},
+    rigid_body::{PhysicsConfig, RigidBody},
+    spawn, Component, SolidCollider, System, V2,
+};
 
 use crate::{
-    attacks::{self, AttackKind},
-    hurtbox::{HurtDirection, Hurtbox, HurtboxProfile, Victim},
-    keyset::Keyset,
+    attacks::AttackKind,
+    controls::{Action, ActionState, Bindings},
+    gamepad::{self, Gamepad},
+    grab::{self, GrabState},
+    hurtbox::{self, Hurtbox, ProjectileState, Victim, HITBOX_LAYER, HURTBOX_LAYER},
+    input_history::InputHistory,
+    ledge::{Ledge, LedgeSide, LedgeState},
+    moves::{Move, MoveSet},
     player::Player,
+    shield::Shield,
     sound_player::SoundPlayer,
     sprite_renderer::Sprite,
     timer::Timer,
 };
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DodgeKind {
+    /// In place, on the ground.
+    Spot,
+    /// Along the ground, 1 for right and -1 for left.
+    Roll { direction: f64 },
+    /// Once per airtime, with an impulse along the held direction.
+    Air,
+}
+
+impl DodgeKind {
+    /// Seconds at the start of the dodge that are invulnerable.
+    pub fn invulnerable_time(&self) -> f64 {
+        match self {
+            DodgeKind::Spot => 0.3,
+            DodgeKind::Roll { .. } => 0.35,
+            DodgeKind::Air => 0.3,
+        }
+    }
+
+    /// Seconds after the invulnerability before the player can act.
+    pub fn endlag(&self) -> f64 {
+        match self {
+            DodgeKind::Spot => 0.15,
+            DodgeKind::Roll { .. } => 0.2,
+            DodgeKind::Air => 0.25,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub enum DodgeState {
-    Dodging(Timer),
+    Dodging { kind: DodgeKind, timer: Timer },
     Cooldown(Timer),
     Ready,
 }
 
 impl DodgeState {
+    pub fn dodging(kind: DodgeKind) -> Self {
+        DodgeState::Dodging {
+            kind,
+            timer: Timer::new(kind.invulnerable_time() + kind.endlag()),
+        }
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        match self {
+            DodgeState::Dodging { kind, timer } => timer.time_passed() < kind.invulnerable_time(),
+            DodgeState::Cooldown(_) | DodgeState::Ready => false,
+        }
+    }
+
+    /// Ends a dodge early, going straight into the cooldown.
+    pub fn cancel(&mut self) {
+        if matches!(self, DodgeState::Dodging { .. }) {
+            *self = DodgeState::Cooldown(Timer::new(2.0));
+        }
+    }
+
     pub fn update(&mut self, delta: f64) {
         match self {
-            DodgeState::Dodging(timer) => {
+            DodgeState::Dodging { timer, .. } => {
                 timer.update(delta);
                 if timer.done() {
                     *self = DodgeState::Cooldown(Timer::new(2.0));
@@ -37,24 +104,84 @@ impl DodgeState {
     }
 }
 
+/// An attack being held, released when the button is let go or the move
+/// is fully charged.
+#[derive(Clone)]
+pub struct Charging {
+    pub kind: AttackKind,
+    pub button: Action,
+    pub timer: Timer,
+}
+
+impl Charging {
+    pub fn progress(&self) -> f64 {
+        if self.timer.duration() <= 0.0 {
+            return 1.0;
+        }
+        min(self.timer.time_passed() / self.timer.duration(), 1.0)
+    }
+}
+
+/// The latest move of a player, until its duration is over. Aerials are
+/// ended early by landing.
+#[derive(Clone)]
+pub struct ActiveMove {
+    pub kind: AttackKind,
+    pub attack: Rc<Move>,
+    pub timer: Timer,
+}
+
 #[derive(Component, Clone)]
 pub struct PlayerInteraction {
-    pub keyset: Keyset,
+    pub bindings: Bindings,
+    pub gamepad: Option<Gamepad>,
+    pub input: ActionState,
+    pub history: InputHistory,
     pub attack_cooldown: f64,
     pub jump_state: JumpState,
     pub dodge_state: DodgeState,
+    pub ledge_state: LedgeState,
+    pub grab_state: GrabState,
+    pub charging: Option<Charging>,
+    pub current_move: Option<ActiveMove>,
+    /// Seconds the player can't act after landing during an aerial.
+    pub landing_lag: f64,
+    /// 1 when facing right, -1 when facing left.
+    pub facing: f64,
+    pub shield: Shield,
+    /// Reset when landing, only one air dodge is allowed per airtime.
+    pub air_dodged: bool,
+    /// Side of the wall last jumped off, each wall can only be jumped off
+    /// once until landing.
+    pub wall_jumped: Option<f64>,
 }
 
 impl PlayerInteraction {
-    pub fn new(keyset: Keyset, attack_cooldown: f64) -> Self {
+    pub fn new(bindings: Bindings, attack_cooldown: f64) -> Self {
         Self {
-            keyset,
+            bindings,
+            gamepad: None,
+            input: ActionState::default(),
+            history: InputHistory::default(),
             attack_cooldown,
             jump_state: JumpState::DoubleJumped,
             dodge_state: DodgeState::Ready,
+            ledge_state: LedgeState::Free,
+            grab_state: GrabState::Free,
+            charging: None,
+            current_move: None,
+            landing_lag: 0.0,
+            facing: 1.0,
+            shield: Shield::new(0.0),
+            air_dodged: false,
+            wall_jumped: None,
         }
     }
 
+    pub fn with_shield(self, shield: Shield) -> Self {
+        Self { shield, ..self }
+    }
+
     pub fn can_jump(&self) -> bool {
         match self.jump_state {
             JumpState::OnGround => true,
@@ -62,137 +189,393 @@ impl PlayerInteraction {
             JumpState::DoubleJumped => false,
         }
     }
+
+    /// Charging, landing lag, shielding and dodging keep the player from
+    /// moving.
+    pub fn rooted(&self) -> bool {
+        self.charging.is_some()
+            || self.landing_lag > 0.0
+            || self.shield.raised()
+            || matches!(self.dodge_state, DodgeState::Dodging { .. })
+    }
+
+    pub fn armored(&self) -> bool {
+        self.current_move
+            .as_ref()
+            .is_some_and(|current| current.attack.armor)
+    }
+
+    /// The move to counter hits with, while in a counter.
+    pub fn counter(&self) -> Option<Rc<Move>> {
+        self.current_move
+            .as_ref()
+            .filter(|current| current.attack.counter)
+            .map(|current| current.attack.clone())
+    }
+
+    pub fn invulnerable(&self) -> bool {
+        self.dodge_state.invulnerable() || self.ledge_state.invulnerable()
+    }
 }
 
 pub struct PlayerInteractionSystem(pub u64);
 impl System for PlayerInteractionSystem {
     fn on_update(&self, ctx: &mut engine::Context, delta: f64) -> Result<(), engine::Error> {
+        gamepad::assign_gamepads(ctx);
         for id in query!(ctx, PlayerInteraction, Victim, RigidBody, SolidCollider) {
+            self.update_input(ctx, delta, id);
+            if ctx.select::<Victim>(id).hitlag.is_some() {
+                continue;
+            }
+            self.update_tumble(ctx, id);
+            self.update_cooldowns(ctx, delta, id);
+            if self.update_grab(ctx, delta, id) {
+                continue;
+            }
+            if self.update_ledge(ctx, delta, id) {
+                continue;
+            }
+            self.update_shield(ctx, delta, id);
             self.update_player_attack(ctx, delta, id)?;
+            self.update_current_move(ctx, delta, id);
             self.update_player_movement(ctx, delta, id)?;
-            self.update_dodge(ctx, delta, id)?;
+            self.update_dodge(ctx, id)?;
         }
         Ok(())
     }
 }
 
 impl PlayerInteractionSystem {
+    fn update_input(&self, ctx: &mut engine::Context, delta: f64, id: u64) {
+        let PlayerInteraction {
+            bindings,
+            gamepad,
+            mut input,
+            ..
+        } = ctx.select::<PlayerInteraction>(id).clone();
+        input.update(ctx, &bindings, gamepad.as_ref());
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        player_interaction.history.record(&input, delta);
+        player_interaction.input = input;
+    }
+
+    /// Tumbling ends by landing, or by acting once the stun is over.
+    fn update_tumble(&self, ctx: &mut engine::Context, id: u64) {
+        let landed = ctx
+            .select::<SolidCollider>(id)
+            .colliding
+            .is_some_and(|dir| dir.facing(OctoDirection::Bottom))
+            && ctx.select::<RigidBody>(id).vel.y >= 0.0;
+        let input = ctx.select::<PlayerInteraction>(id).input.clone();
+        let victim = ctx.select::<Victim>(id);
+        let acted = victim.stunned.is_none()
+            && Action::ALL
+                .into_iter()
+                .any(|action| input.just_pressed(action));
+        if landed || acted {
+            victim.tumbling = false;
+        }
+    }
+
+    /// Shields can only be raised on the ground, and not while attacking,
+    /// dodging or stunned.
+    fn update_shield(&self, ctx: &mut engine::Context, delta: f64, id: u64) {
+        let stunned = ctx.select::<Victim>(id).stunned.is_some();
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        let held = player_interaction.input.pressed(Action::Shield)
+            && !stunned
+            && matches!(player_interaction.jump_state, JumpState::OnGround)
+            && matches!(player_interaction.dodge_state, DodgeState::Ready)
+            && player_interaction.charging.is_none()
+            && player_interaction.current_move.is_none()
+            && player_interaction.landing_lag <= 0.0;
+        if player_interaction.shield.update(delta, held) {
+            ctx.select::<Victim>(id).stunned = Some(Shield::BREAK_STUN);
+        }
+    }
+
+    /// Grabs aren't hurtboxes, so they go through shields and dodges.
+    /// Returns true while holding or held, which replaces every other
+    /// action.
+    fn update_grab(&self, ctx: &mut engine::Context, delta: f64, id: u64) -> bool {
+        match ctx.select::<PlayerInteraction>(id).grab_state.clone() {
+            GrabState::Free => self.start_grab(ctx, id),
+            GrabState::Holding {
+                target,
+                mut timer,
+                mut pummel,
+            } => {
+                timer.update(delta);
+                pummel.update(delta);
+                self.update_holding(ctx, id, target, timer, pummel);
+                true
+            }
+            GrabState::Held { .. } => true,
+        }
+    }
+
+    fn start_grab(&self, ctx: &mut engine::Context, id: u64) -> bool {
+        let player_interaction = ctx.select::<PlayerInteraction>(id).clone();
+        let stunned = ctx.select::<Victim>(id).stunned.is_some();
+        let busy = player_interaction.charging.is_some()
+            || player_interaction.current_move.is_some()
+            || player_interaction.landing_lag > 0.0
+            || player_interaction.attack_cooldown >= 0.0
+            || matches!(player_interaction.dodge_state, DodgeState::Dodging { .. })
+            || !matches!(player_interaction.ledge_state, LedgeState::Free)
+            || !matches!(player_interaction.jump_state, JumpState::OnGround);
+        if stunned || busy {
+            return false;
+        }
+        if !ctx
+            .select::<PlayerInteraction>(id)
+            .history
+            .take_buffered(Action::Grab)
+        {
+            return false;
+        }
+
+        let body = ctx.select::<RigidBody>(id).clone();
+        let facing = player_interaction.facing;
+        let target = query!(ctx, PlayerInteraction, RigidBody)
+            .into_iter()
+            .filter(|target| *target != id)
+            .find(|target| {
+                let target_interaction = ctx.select::<PlayerInteraction>(*target);
+                if !matches!(target_interaction.grab_state, GrabState::Free)
+                    || target_interaction.ledge_state.invulnerable()
+                {
+                    return false;
+                }
+                grab::in_grab_range(&body, facing, ctx.select::<RigidBody>(*target))
+            });
+        let Some(target) = target else {
+            // missed grabs leave an opening
+            let whiff_lag = 0.5;
+            ctx.select::<PlayerInteraction>(id).attack_cooldown = whiff_lag;
+            return false;
+        };
+
+        self.despawn_hurtboxes(ctx, target);
+        let target_interaction = ctx.select::<PlayerInteraction>(target);
+        target_interaction.grab_state = GrabState::Held { by: id };
+        target_interaction.charging = None;
+        target_interaction.current_move = None;
+        target_interaction.shield.lower();
+        target_interaction.dodge_state.cancel();
+        if matches!(target_interaction.ledge_state, LedgeState::Hanging { .. }) {
+            target_interaction.ledge_state = LedgeState::released();
+        }
+        ctx.select::<Sprite>(target).set_opacity(1.0);
+        ctx.select::<PlayerInteraction>(id).grab_state = GrabState::holding(target);
+        ctx.select::<RigidBody>(id).vel = V2::new(0.0, 0.0);
+        true
+    }
+
+    /// Pummels with the attack button, throws in the pressed direction and
+    /// lets go when the timer is done or the holder is stunned.
+    fn update_holding(
+        &self,
+        ctx: &mut engine::Context,
+        id: u64,
+        target: u64,
+        timer: Timer,
+        pummel: Timer,
+    ) {
+        let player_interaction = ctx.select::<PlayerInteraction>(id).clone();
+        let body = ctx.select::<RigidBody>(id).clone();
+        let facing = player_interaction.facing;
+
+        let target_body = ctx.select::<RigidBody>(target);
+        target_body.pos = grab::held_pos(&body, facing, target_body.size);
+        target_body.vel = V2::new(0.0, 0.0);
+
+        if timer.done() || ctx.select::<Victim>(id).stunned.is_some() {
+            self.release(ctx, id, target);
+            return;
+        }
+
+        let input = &player_interaction.input;
+        let forward = input.horizontal() * facing;
+        let throw = if input.just_pressed(Action::Up) {
+            Some(AttackKind::ThrowUp)
+        } else if input.just_pressed(Action::Down) {
+            Some(AttackKind::ThrowDown)
+        } else if forward > 0.5 {
+            Some(AttackKind::ThrowForward)
+        } else if forward < -0.5 {
+            Some(AttackKind::ThrowBack)
+        } else {
+            None
+        };
+        if let Some(kind) = throw {
+            self.release(ctx, id, target);
+            let throw = self.grab_move(ctx, id, kind);
+            hurtbox::hit(ctx, throw.as_ref(), Some(id), target, &body);
+            let agility = ctx.select::<Player>(id).hero.agility_points;
+            ctx.select::<PlayerInteraction>(id).attack_cooldown = throw.cooldown(agility);
+            return;
+        }
+
+        let history = &mut ctx.select::<PlayerInteraction>(id).history;
+        let pummel = if pummel.done() && history.take_buffered(Action::LightAttack) {
+            let attack = self.grab_move(ctx, id, AttackKind::Pummel);
+            hurtbox::hit(ctx, attack.as_ref(), Some(id), target, &body);
+            let agility = ctx.select::<Player>(id).hero.agility_points;
+            Timer::new(attack.cooldown(agility))
+        } else {
+            pummel
+        };
+        ctx.select::<PlayerInteraction>(id).grab_state = GrabState::Holding {
+            target,
+            timer,
+            pummel,
+        };
+    }
+
+    /// Pummels and throws, mirrored to the holder's facing and played with
+    /// their sound.
+    fn grab_move(&self, ctx: &mut engine::Context, id: u64, kind: AttackKind) -> Rc<Move> {
+        let hero = ctx.select::<Player>(id).hero.kind.clone();
+        let attack = ctx.select_one::<MoveSet>().get(&hero, kind);
+        let facing = ctx.select::<PlayerInteraction>(id).facing;
+        let attack = if kind.uses_facing() && facing < 0.0 {
+            Rc::new(attack.mirrored())
+        } else {
+            attack
+        };
+        if let Some(sound) = &attack.sound {
+            let sound_player = ctx.select_one::<SoundPlayer>();
+            sound_player.set_effect_volume(sound.volume);
+            sound_player.play_effect(&sound.path);
+        }
+        attack
+    }
+
+    fn release(&self, ctx: &mut engine::Context, id: u64, target: u64) {
+        ctx.select::<PlayerInteraction>(id).grab_state = GrabState::Free;
+        ctx.select::<PlayerInteraction>(target).grab_state = GrabState::Free;
+    }
+
+    /// Spawns the hurtbox of the move and returns the move.
     fn spawn_attack(
         &self,
         ctx: &mut engine::Context,
-        attack_kind: AttackKind,
+        kind: AttackKind,
+        charge: f64,
         id: u64,
         body: &RigidBody,
-    ) {
-        let sound_player = ctx.select_one::<SoundPlayer>();
-        sound_player.set_effect_volume(0.2);
-        sound_player.play_effect("assets/sounds/swoosh.ogg");
-
-        let attack_size = self.attack_size(&attack_kind);
-        let pos = self.attack_pos(&attack_kind, body, attack_size);
-        let vel = self.attack_vel(&attack_kind, body.vel);
-        let textures = self.attack_textures(ctx, &attack_kind);
-        let profile = self.attack_profile(&attack_kind).into();
+    ) -> Rc<Move> {
+        let hero = ctx.select::<Player>(id).hero.kind.clone();
+        let attack = ctx.select_one::<MoveSet>().get(&hero, kind).charged(charge);
+        let facing = ctx.select::<PlayerInteraction>(id).facing;
+        let attack = Rc::new(if kind.uses_facing() && facing < 0.0 {
+            attack.mirrored()
+        } else {
+            attack
+        });
+        if let Some(sound) = &attack.sound {
+            let sound_player = ctx.select_one::<SoundPlayer>();
+            sound_player.set_effect_volume(sound.volume);
+            sound_player.play_effect(&sound.path);
+        }
+
+        let pos = attack.pos(body);
+        let mut hurtbox_body = RigidBody::new()
+            .with_pos(pos)
+            .with_vel(attack.vel(body))
+            .with_size(attack.size());
+        let mut mask = HITBOX_LAYER;
+        if let Some(projectile) = &attack.projectile {
+            // projectiles also overlap solid colliders, to despawn on them
+            mask |= DEFAULT_LAYER;
+            if projectile.gravity_scale > 0.0 {
+                hurtbox_body = hurtbox_body
+                    .with_gravity()
+                    .with_gravity_scale(projectile.gravity_scale);
+            }
+        }
+        let projectile = attack
+            .projectile
+            .as_ref()
+            .map(|projectile| ProjectileState {
+                origin: pos,
+                range: projectile.range,
+                pierce: projectile.pierce,
+                hits: 0,
+                reflectable: projectile.reflectable,
+            });
+
+        let textures = attack
+            .frames
+            .iter()
+            .map(|frame| ctx.load_texture(&frame.texture).unwrap())
+            .collect::<Vec<_>>();
         spawn!(
             ctx,
-            Sprite::new(textures[0]),
-            // .size((256.0, 64.0))
-            // .offset((0.0, -16.0)),
-            RigidBody::new()
-                .with_pos(pos)
-                .with_vel(vel)
-                .with_size(attack_size),
+            Sprite::new(textures[0]).flip_horizontal(attack.flipped),
+            hurtbox_body,
+            Trigger::new().layer(HURTBOX_LAYER).mask(mask),
             Hurtbox {
                 owner: Some(id),
-                timer: Timer::new(0.3),
+                timer: Timer::new(attack.duration),
+                startup: attack.startup,
+                follow: attack.follow.then(|| attack.pos(body) - body.pos),
                 textures,
-                profile,
+                frame_hurtboxes: attack.frame_hurtboxes(),
+                projectile,
+                profile: attack.clone(),
             }
         );
-    }
-
-    fn attack_size(&self, attack_kind: &AttackKind) -> V2 {
-        match attack_kind {
-            AttackKind::Up => V2::new(128.0, 64.0),
-            AttackKind::Down => V2::new(128.0 * 2.0, 32.0),
-            AttackKind::Left => V2::new(64.0, 128.0),
-            AttackKind::Right => V2::new(64.0, 128.0),
+        if let Some(vel) = attack.attacker_vel {
+            ctx.select::<RigidBody>(id).vel = vel.into();
         }
+        ctx.select::<PlayerInteraction>(id).current_move = Some(ActiveMove {
+            kind,
+            attack: attack.clone(),
+            timer: Timer::new(attack.duration),
+        });
+        attack
     }
 
-    fn attack_pos(&self, attack_kind: &AttackKind, body: &RigidBody, attack_size: V2) -> V2 {
-        match attack_kind {
-            AttackKind::Up => V2::new(
-                body.pos.x + (body.size.x - attack_size.x) / 2.0,
-                body.pos.y - attack_size.y,
-            ),
-            AttackKind::Down => V2::new(
-                body.pos.x + (body.size.x - attack_size.x) / 2.0,
-                body.pos.y + body.size.y - attack_size.y,
-            ),
-            AttackKind::Left => V2::new(
-                body.pos.x - attack_size.x,
-                body.pos.y + (body.size.y - attack_size.y) / 2.0,
-            ),
-            AttackKind::Right => V2::new(
-                body.pos.x + body.size.x,
-                body.pos.y + (body.size.y - attack_size.y) / 2.0,
-            ),
+    /// Ends the player's attacks, projectiles already thrown keep flying.
+    fn despawn_hurtboxes(&self, ctx: &mut engine::Context, id: u64) {
+        for hurtbox_id in query!(ctx, Hurtbox, RigidBody) {
+            let hurtbox = ctx.select::<Hurtbox>(hurtbox_id);
+            if hurtbox.projectile.is_none() && hurtbox.owner.is_some_and(|owner| owner == id) {
+                ctx.despawn(hurtbox_id);
+            };
         }
     }
 
-    fn attack_vel(&self, attack_kind: &AttackKind, vel: V2) -> V2 {
-        match attack_kind {
-            AttackKind::Up => V2::new(0.0, 0.0),
-            AttackKind::Down => V2::new(0.0, 0.0),
-            AttackKind::Left => vel.div_comps(2.0),
-            AttackKind::Right => vel.div_comps(2.0),
+    /// Aerials are picked by the held direction relative to the facing.
+    fn attack_kind(&self, player_interaction: &PlayerInteraction) -> AttackKind {
+        let input = &player_interaction.input;
+        let forward = input.horizontal() * player_interaction.facing;
+        let airborne = !matches!(player_interaction.jump_state, JumpState::OnGround);
+        if airborne {
+            if input.pressed(Action::Down) {
+                AttackKind::DownAir
+            } else if input.pressed(Action::Up) {
+                AttackKind::UpAir
+            } else if forward > 0.5 {
+                AttackKind::ForwardAir
+            } else if forward < -0.5 {
+                AttackKind::BackAir
+            } else {
+                AttackKind::NeutralAir
+            }
+        } else if input.pressed(Action::Down) {
+            AttackKind::Down
+        } else if input.horizontal() < -0.5 {
+            AttackKind::Left
+        } else if input.horizontal() > 0.5 {
+            AttackKind::Right
+        } else {
+            AttackKind::Up
         }
     }
 
-    fn attack_textures(
-        &self,
-        ctx: &mut engine::Context,
-        attack_kind: &AttackKind,
-    ) -> Vec<engine::Texture> {
-        match attack_kind {
-            AttackKind::Up => vec![
-                "assets/attacks/up_0.png".to_string(),
-                "assets/attacks/up_1.png".to_string(),
-                "assets/attacks/up_2.png".to_string(),
-                "assets/attacks/up_3.png".to_string(),
-                "assets/attacks/up_4.png".to_string(),
-            ],
-            AttackKind::Down => vec![
-                "assets/attacks/down_0.png".to_string(),
-                "assets/attacks/down_1.png".to_string(),
-                "assets/attacks/down_2.png".to_string(),
-                "assets/attacks/down_3.png".to_string(),
-                "assets/attacks/down_4.png".to_string(),
-                "assets/attacks/down_5.png".to_string(),
-                "assets/attacks/down_6.png".to_string(),
-                "assets/attacks/down_7.png".to_string(),
-            ],
-            AttackKind::Left => vec![
-                "assets/attacks/left_0.png".to_string(),
-                "assets/attacks/left_1.png".to_string(),
-                "assets/attacks/left_2.png".to_string(),
-                "assets/attacks/left_3.png".to_string(),
-                "assets/attacks/left_4.png".to_string(),
-            ],
-            AttackKind::Right => vec![
-                "assets/attacks/right_0.png".to_string(),
-                "assets/attacks/right_1.png".to_string(),
-                "assets/attacks/right_2.png".to_string(),
-                "assets/attacks/right_3.png".to_string(),
-                "assets/attacks/right_4.png".to_string(),
-            ],
-        }
-        .into_iter()
-        .map(|path| ctx.load_texture(path).unwrap())
-        .collect::<Vec<_>>()
-    }
-
     fn update_player_attack(
         &self,
         ctx: &mut engine::Context,
@@ -200,97 +583,140 @@ impl PlayerInteractionSystem {
         id: u64,
     ) -> Result<(), engine::Error> {
         let player_attack = ctx.select::<PlayerInteraction>(id).clone();
-        let keyset = player_attack.keyset;
-        let right_pressed = ctx.key_pressed(keyset.right());
-        let left_pressed = ctx.key_pressed(keyset.left());
-        let down_pressed = ctx.key_pressed(keyset.down());
-        let light_attack_pressed = ctx.key_just_pressed(keyset.light_attack());
+        let input = player_attack.input.clone();
         let victim = ctx.select::<Victim>(id).clone();
         let body = ctx.select::<RigidBody>(id).clone();
 
-        if matches!(player_attack.dodge_state, DodgeState::Dodging(_)) {
+        if matches!(player_attack.dodge_state, DodgeState::Dodging { .. }) {
             return Ok(());
         }
 
         if victim.stunned.is_some() {
-            for hurtbox_id in query!(ctx, Hurtbox, RigidBody) {
-                let hurtbox = ctx.select::<Hurtbox>(hurtbox_id);
-                if hurtbox.owner.is_some_and(|owner| owner == id) {
-                    ctx.despawn(hurtbox_id);
-                };
+            self.despawn_hurtboxes(ctx, id);
+            let player_attack = ctx.select::<PlayerInteraction>(id);
+            player_attack.charging = None;
+            player_attack.current_move = None;
+            return Ok(());
+        }
+
+        if let Some(mut charging) = player_attack.charging {
+            charging.timer.update(delta);
+            if input.pressed(charging.button) && !charging.timer.done() {
+                ctx.select::<PlayerInteraction>(id).charging = Some(charging);
+                return Ok(());
             }
+            let charge = charging.timer.time_passed();
+            let attack = self.spawn_attack(ctx, charging.kind, charge, id, &body);
+            let agility = ctx.select::<Player>(id).hero.agility_points;
+            let player_attack = ctx.select::<PlayerInteraction>(id);
+            player_attack.charging = None;
+            player_attack.attack_cooldown = attack.cooldown(agility);
             return Ok(());
         }
 
         if player_attack.attack_cooldown >= 0.0 {
-            let player_attack = ctx.select::<PlayerInteraction>(id);
-            player_attack.attack_cooldown -= delta;
             return Ok(());
         }
 
-        if !light_attack_pressed {
+        if player_attack.shield.raised() {
             return Ok(());
         }
 
-        if down_pressed {
-            self.spawn_attack(ctx, AttackKind::Down, id, &body);
-        } else if left_pressed && !right_pressed {
-            self.spawn_attack(ctx, AttackKind::Left, id, &body);
-        } else if right_pressed && !left_pressed {
-            self.spawn_attack(ctx, AttackKind::Right, id, &body);
+        let kind = self.attack_kind(&player_attack);
+
+        // presses made during stun, dodges or the cooldown are kept in the
+        // history, so they still come out if they were recent enough
+        let history = &mut ctx.select::<PlayerInteraction>(id).history;
+        let (kind, button) = if history.take_buffered(Action::Special) {
+            (AttackKind::Special, Action::Special)
+        } else if history.take_buffered(Action::HeavyAttack) {
+            (kind.heavy(), Action::HeavyAttack)
+        } else if history.take_buffered(Action::LightAttack) {
+            (kind, Action::LightAttack)
         } else {
-            self.spawn_attack(ctx, AttackKind::Up, id, &body);
-        }
-        let agility = ctx.select::<Player>(id).hero.agility_points;
-        let player_attack = ctx.select::<PlayerInteraction>(id);
+            return Ok(());
+        };
 
-        let min_attack_cooldown = 0.4;
-        let max_attack_cooldown = 1.0;
+        let hero = ctx.select::<Player>(id).hero.kind.clone();
+        let charge_time = ctx.select_one::<MoveSet>().get(&hero, kind).charge_time();
+        if charge_time > 0.0 {
+            ctx.select::<PlayerInteraction>(id).charging = Some(Charging {
+                kind,
+                button,
+                timer: Timer::new(charge_time),
+            });
+            return Ok(());
+        }
 
-        let attack_cooldown = (max_attack_cooldown - min_attack_cooldown)
-            * (1.0 - agility as f64 / 24.0)
-            + min_attack_cooldown;
+        let attack = self.spawn_attack(ctx, kind, 0.0, id, &body);
 
-        player_attack.attack_cooldown = attack_cooldown;
+        let agility = ctx.select::<Player>(id).hero.agility_points;
+        let player_attack = ctx.select::<PlayerInteraction>(id);
+        player_attack.attack_cooldown = attack.cooldown(agility);
 
         Ok(())
     }
 
+    /// Landing ends an aerial, with landing lag unless it lands in an
+    /// auto-cancel window.
+    fn update_current_move(&self, ctx: &mut engine::Context, delta: f64, id: u64) {
+        let grounded = ctx
+            .select::<SolidCollider>(id)
+            .colliding
+            .is_some_and(|dir| dir.facing(OctoDirection::Bottom));
+        let falling = ctx.select::<RigidBody>(id).vel.y >= 0.0;
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        if player_interaction.landing_lag > 0.0 {
+            player_interaction.landing_lag -= delta;
+        }
+        let Some(mut current) = player_interaction.current_move.take() else {
+            return;
+        };
+        current.timer.update(delta);
+        if current.kind.is_aerial() && grounded && falling {
+            let lag = current.attack.landing_lag(current.timer.time_passed());
+            player_interaction.landing_lag = lag;
+            player_interaction.attack_cooldown = max(player_interaction.attack_cooldown, lag);
+            self.despawn_hurtboxes(ctx, id);
+        } else if !current.timer.done() {
+            player_interaction.current_move = Some(current);
+        }
+    }
+
     fn update_player_movement(
         &self,
         ctx: &mut engine::Context,
         delta: f64,
         id: u64,
     ) -> Result<(), engine::Error> {
-        let keyset = ctx.select::<PlayerInteraction>(id).clone().keyset;
-
-        let right_pressed = ctx.key_pressed(keyset.right());
-        let left_pressed = ctx.key_pressed(keyset.left());
-        let down_pressed = ctx.key_pressed(keyset.down());
-
-        let up_pressed = ctx.key_just_pressed(keyset.up());
+        let input = ctx.select::<PlayerInteraction>(id).input.clone();
 
         let collider = ctx.select::<SolidCollider>(id).clone();
         let victim = ctx.select::<Victim>(id).clone();
         let player_movement = ctx.select::<PlayerInteraction>(id).clone();
         let agility = ctx.select::<Player>(id).hero.agility_points;
+        let physics = PhysicsConfig::current(ctx);
         let body = ctx.select::<RigidBody>(id);
 
-        if victim.stunned.is_some() {
+        if victim.stunned.is_some() || player_movement.rooted() {
             return Ok(());
         }
 
-        let acceleration = 300.0 * delta * 8.0 * (1.0 + agility as f64 / 24.0);
-        let max_speed = 300.0 * (1.0 + agility as f64 / 24.0);
+        let horizontal = input.horizontal();
+        let deflection = horizontal.abs();
+        let acceleration = 300.0 * delta * 8.0 * (1.0 + agility as f64 / 24.0) * deflection;
+        let max_speed = 300.0 * (1.0 + agility as f64 / 24.0) * deflection;
 
-        if right_pressed && !left_pressed && body.vel.x < max_speed {
+        if horizontal > 0.0 && body.vel.x < max_speed {
             body.vel.x += acceleration;
-        } else if left_pressed && !right_pressed && body.vel.x > (-max_speed) {
+        } else if horizontal < 0.0 && body.vel.x > (-max_speed) {
             body.vel.x -= acceleration;
         }
 
-        if down_pressed && body.vel.y < 800.0 {
-            body.vel.y += 3200.0 * delta;
+        // fast fall, twice as fast as falling normally
+        let fast_fall_scale = 2.0 * body.gravity_scale;
+        if input.pressed(Action::Down) && body.vel.y < physics.terminal_velocity * fast_fall_scale {
+            body.vel.y += physics.gravity.y * fast_fall_scale * delta;
         }
 
         if collider
@@ -300,34 +726,220 @@ impl PlayerInteractionSystem {
             let player_movement = ctx.select::<PlayerInteraction>(id);
             if !matches!(player_movement.jump_state, JumpState::OnGround) {
                 player_movement.jump_state = JumpState::OnGround;
+                player_movement.air_dodged = false;
+                player_movement.wall_jumped = None;
                 let sound_player = ctx.select_one::<SoundPlayer>();
                 sound_player.play_effect("assets/sounds/click.ogg");
             }
         }
 
-        if up_pressed && player_movement.can_jump() {
+        let grounded = collider
+            .colliding
+            .is_some_and(|dir| dir.facing(OctoDirection::Bottom));
+        if !grounded {
+            self.update_wall(ctx, id);
+        } else if horizontal.abs() > 0.5 {
+            ctx.select::<PlayerInteraction>(id).facing = horizontal.signum();
+        }
+
+        // down + jump, or down twice, drops through one-way platforms
+        let drop_through_window = 0.3;
+        let history = &player_movement.history;
+        let drop_through = collider.on_shallow
+            && input.pressed(Action::Down)
+            && (history.pressed_within(Action::Up, history.buffer_window)
+                || history.motion_performed(&[Action::Down, Action::Down], drop_through_window));
+        if drop_through {
+            let player_movement = ctx.select::<PlayerInteraction>(id);
+            player_movement.history.take_buffered(Action::Up);
+            player_movement.jump_state = JumpState::Jumped;
+            ctx.select::<SolidCollider>(id).drop_through(0.25);
+        }
+
+        if player_movement.can_jump()
+            && ctx
+                .select::<PlayerInteraction>(id)
+                .history
+                .take_buffered(Action::Up)
+        {
             let body = ctx.select::<RigidBody>(id);
-            body.vel.y = -800.0;
+            body.vel.y = -physics.jump_speed;
             let player_movement = ctx.select::<PlayerInteraction>(id);
             player_movement.jump_state = player_movement.jump_state.next();
         }
         Ok(())
     }
 
-    fn update_dodge(
-        &self,
-        ctx: &mut engine::Context,
-        delta: f64,
-        id: u64,
-    ) -> Result<(), engine::Error> {
+    /// Wall slides while holding towards a wall, and jumps off it.
+    fn update_wall(&self, ctx: &mut engine::Context, id: u64) {
+        let Some(wall) = self.wall_side(ctx, id) else {
+            return;
+        };
+        let input = ctx.select::<PlayerInteraction>(id).input.clone();
+        if input.horizontal() * wall > 0.5 {
+            let wall_slide_speed = 150.0;
+            let body = ctx.select::<RigidBody>(id);
+            body.vel.y = min(body.vel.y, wall_slide_speed);
+        }
+
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        if player_interaction.wall_jumped == Some(wall)
+            || !player_interaction.history.take_buffered(Action::Up)
+        {
+            return;
+        }
+        player_interaction.jump_state = JumpState::Jumped;
+        player_interaction.wall_jumped = Some(wall);
+        let jump_speed = PhysicsConfig::current(ctx).jump_speed;
+        let body = ctx.select::<RigidBody>(id);
+        body.vel = V2::new(-wall * 500.0, -jump_speed);
+    }
+
+    /// Direction of a wall right next to the player, if any.
+    fn wall_side(&self, ctx: &mut engine::Context, id: u64) -> Option<f64> {
+        let body = ctx.select::<RigidBody>(id).clone();
+        let mut filter = QueryFilter::new().without_shallow();
+        for player_id in query!(ctx, PlayerInteraction) {
+            filter = filter.excluding(player_id);
+        }
+        let reach = 4.0;
+        [-1.0, 1.0].into_iter().find(|direction| {
+            let motion = V2::new(direction * reach, 0.0);
+            raycast::sweep(ctx, body.pos, body.size, motion, &filter)
+                .is_some_and(|hit| hit.normal.x.abs() > 0.7)
+        })
+    }
+
+    /// Grabs ledges when falling past them and handles getting up.
+    /// Returns whether the player is on a ledge this update.
+    fn update_ledge(&self, ctx: &mut engine::Context, delta: f64, id: u64) -> bool {
         let player_interaction = ctx.select::<PlayerInteraction>(id);
-        let keyset = player_interaction.keyset.clone();
-        let dodge_state = &mut player_interaction.dodge_state;
+        player_interaction.ledge_state.update(delta);
+        match player_interaction.ledge_state.clone() {
+            LedgeState::Hanging { ledge, .. } => {
+                self.update_hanging(ctx, id, &ledge);
+                true
+            }
+            LedgeState::Free => self.grab_ledge(ctx, id),
+            LedgeState::Released(_) => false,
+        }
+    }
+
+    fn grab_ledge(&self, ctx: &mut engine::Context, id: u64) -> bool {
+        let player_interaction = ctx.select::<PlayerInteraction>(id).clone();
+        let stunned = ctx.select::<Victim>(id).stunned.is_some();
+        let grounded = ctx
+            .select::<SolidCollider>(id)
+            .colliding
+            .is_some_and(|dir| dir.facing(OctoDirection::Bottom));
+        let falling = ctx.select::<RigidBody>(id).vel.y >= 0.0;
+        let charging = player_interaction.charging.is_some();
+        let holding_down = player_interaction.input.pressed(Action::Down);
+        let tumbling = ctx.select::<Victim>(id).tumbling;
+        if stunned || charging || tumbling || grounded || !falling || holding_down {
+            return false;
+        }
+
+        let Some(ledge) = query!(ctx, Ledge, Trigger)
+            .into_iter()
+            .find(|ledge_id| ctx.select::<Trigger>(*ledge_id).overlapping().contains(&id))
+            .map(|ledge_id| ctx.select::<Ledge>(ledge_id).clone())
+        else {
+            return false;
+        };
 
-        dodge_state.update(delta);
+        let body = ctx.select::<RigidBody>(id);
+        body.pos = ledge.hang_pos(body.size);
+        body.vel = V2::new(0.0, 0.0);
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        player_interaction.jump_state = JumpState::OnGround;
+        player_interaction.air_dodged = false;
+        player_interaction.wall_jumped = None;
+        player_interaction.ledge_state = LedgeState::hanging(ledge);
+        player_interaction.dodge_state.cancel();
+        ctx.select::<Sprite>(id).set_opacity(1.0);
+        true
+    }
 
-        match dodge_state {
-            DodgeState::Dodging(_) => return Ok(()),
+    fn update_hanging(&self, ctx: &mut engine::Context, id: u64, ledge: &Ledge) {
+        let victim = ctx.select::<Victim>(id);
+        let hit = victim.stunned.is_some() || victim.hitlag.is_some();
+        let mut ledge_state = ctx.select::<PlayerInteraction>(id).ledge_state.clone();
+        let hanging = ledge_state.hold(ctx.select::<RigidBody>(id), hit);
+        ctx.select::<PlayerInteraction>(id).ledge_state = ledge_state;
+        if !hanging {
+            return;
+        }
+        let size = ctx.select::<RigidBody>(id).size;
+
+        let jump_speed = PhysicsConfig::current(ctx).jump_speed;
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        let toward_stage = player_interaction.input.horizontal() * ledge.inward();
+        let history = &mut player_interaction.history;
+        if history.take_buffered(Action::Up) {
+            player_interaction.jump_state = JumpState::Jumped;
+            player_interaction.ledge_state = LedgeState::released();
+            ctx.select::<RigidBody>(id).vel.y = -jump_speed;
+        } else if history.take_buffered(Action::LightAttack) {
+            player_interaction.attack_cooldown = 0.5;
+            player_interaction.ledge_state = LedgeState::released();
+            let body = ctx.select::<RigidBody>(id);
+            body.pos = ledge.getup_pos(size);
+            let body = body.clone();
+            let kind = match ledge.side {
+                LedgeSide::Left => AttackKind::Right,
+                LedgeSide::Right => AttackKind::Left,
+            };
+            self.spawn_attack(ctx, kind, 0.0, id, &body);
+        } else if history.take_buffered(Action::Dodge) {
+            let can_dodge = matches!(player_interaction.dodge_state, DodgeState::Ready);
+            if can_dodge {
+                player_interaction.dodge_state = DodgeState::dodging(DodgeKind::Spot);
+            }
+            player_interaction.ledge_state = LedgeState::released();
+            let body = ctx.select::<RigidBody>(id);
+            body.pos = ledge.getup_pos(size).add_x(ledge.inward() * size.x);
+            if can_dodge {
+                ctx.select::<Sprite>(id).set_opacity(0.5);
+            }
+        } else if toward_stage > 0.5 {
+            player_interaction.ledge_state = LedgeState::released();
+            ctx.select::<RigidBody>(id).pos = ledge.getup_pos(size);
+        } else if toward_stage < -0.5 || player_interaction.input.pressed(Action::Down) {
+            player_interaction.ledge_state = LedgeState::released();
+        }
+    }
+
+    /// Runs down the attack cooldown and dodge timers, which keep going
+    /// while the player is held or hanging from a ledge.
+    fn update_cooldowns(&self, ctx: &mut engine::Context, delta: f64, id: u64) {
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        if player_interaction.attack_cooldown >= 0.0 {
+            player_interaction.attack_cooldown -= delta;
+        }
+        player_interaction.dodge_state.update(delta);
+    }
+
+    /// Rolls and spot dodges on the ground, air dodges along the held
+    /// direction in the air. Shields can be dodged out of.
+    fn update_dodge(&self, ctx: &mut engine::Context, id: u64) -> Result<(), engine::Error> {
+        let player_interaction = ctx.select::<PlayerInteraction>(id);
+        match player_interaction.dodge_state.clone() {
+            DodgeState::Dodging { kind, .. } => {
+                let invulnerable = player_interaction.dodge_state.invulnerable();
+                if let DodgeKind::Roll { direction } = kind {
+                    let roll_speed = 600.0;
+                    let body = ctx.select::<RigidBody>(id);
+                    body.vel.x = if invulnerable {
+                        direction * roll_speed
+                    } else {
+                        0.0
+                    };
+                }
+                let sprite = ctx.select::<Sprite>(id);
+                sprite.set_opacity(if invulnerable { 0.5 } else { 1.0 });
+                return Ok(());
+            }
             DodgeState::Cooldown(_) => {
                 let sprite = ctx.select::<Sprite>(id);
                 sprite.set_opacity(1.0);
@@ -336,36 +948,52 @@ impl PlayerInteractionSystem {
             DodgeState::Ready => (),
         }
 
-        let dodge_pressed = ctx.key_just_pressed(keyset.dodge());
-
+        let busy = player_interaction.charging.is_some() || player_interaction.landing_lag > 0.0;
         let victim = ctx.select::<Victim>(id);
-
-        if !dodge_pressed || victim.stunned.is_some() {
+        if victim.stunned.is_some() || busy {
             return Ok(());
         }
 
+        let grounded = ctx
+            .select::<SolidCollider>(id)
+            .colliding
+            .is_some_and(|dir| dir.facing(OctoDirection::Bottom));
         let player_interaction = ctx.select::<PlayerInteraction>(id);
-        let dodge_state = &mut player_interaction.dodge_state;
-        *dodge_state = DodgeState::Dodging(Timer::new(0.5));
+        if !grounded && player_interaction.air_dodged {
+            return Ok(());
+        }
+        if !player_interaction.history.take_buffered(Action::Dodge) {
+            return Ok(());
+        }
+
+        let input = player_interaction.input.clone();
+        let horizontal = input.horizontal();
+        let kind = if !grounded {
+            DodgeKind::Air
+        } else if horizontal.abs() > 0.5 {
+            DodgeKind::Roll {
+                direction: horizontal.signum(),
+            }
+        } else {
+            DodgeKind::Spot
+        };
+        player_interaction.dodge_state = DodgeState::dodging(kind);
+        player_interaction.shield.lower();
+
+        if kind == DodgeKind::Air {
+            player_interaction.air_dodged = true;
+            let held = V2::new(horizontal, input.vertical());
+            let air_dodge_speed = 700.0;
+            if held.len() > 0.3 {
+                ctx.select::<RigidBody>(id).vel = held.normalized().extend(air_dodge_speed);
+            }
+        }
 
         let sprite = ctx.select::<Sprite>(id);
         sprite.set_opacity(0.5);
 
         Ok(())
     }
-
-    fn attack_profile(&self, attack_kind: &AttackKind) -> Box<dyn HurtboxProfile> {
-        match attack_kind {
-            AttackKind::Up => Box::new(attacks::UpAttackProfile),
-            AttackKind::Down => Box::new(attacks::DownAttackProfile),
-            AttackKind::Left => Box::new(attacks::SideAttackProfile {
-                direction: HurtDirection::Left,
-            }),
-            AttackKind::Right => Box::new(attacks::SideAttackProfile {
-                direction: HurtDirection::Right,
-            }),
-        }
-    }
 }
 
 #[derive(Clone)]
@@ -384,3 +1012,29 @@ impl JumpState {
         }
     }
 }
+
+#[test]
+fn test_dodge_invulnerability_and_endlag() {
+    let kind = DodgeKind::Roll { direction: -1.0 };
+    let mut dodge = DodgeState::dodging(kind);
+    assert!(dodge.invulnerable());
+
+    dodge.update(kind.invulnerable_time() + 0.01);
+    assert!(!dodge.invulnerable());
+    assert!(matches!(dodge, DodgeState::Dodging { .. }));
+
+    dodge.update(kind.endlag());
+    assert!(matches!(dodge, DodgeState::Cooldown(_)));
+}
+
+#[test]
+fn test_dodge_cancel() {
+    let mut dodge = DodgeState::dodging(DodgeKind::Spot);
+    dodge.cancel();
+    assert!(!dodge.invulnerable());
+    assert!(matches!(dodge, DodgeState::Cooldown(_)));
+
+    let mut ready = DodgeState::Ready;
+    ready.cancel();
+    assert!(matches!(ready, DodgeState::Ready));
+}
@@ -0,0 +1,123 @@
+/// Held with the shield button. Hits wear the shield down instead of
+/// hurting the player, and it regenerates while lowered.
+#[derive(Clone, Debug)]
+pub struct Shield {
+    pub health: f64,
+    pub max_health: f64,
+    /// Seconds since the shield was raised, `None` while lowered.
+    raised: Option<f64>,
+}
+
+impl Shield {
+    /// Seconds after raising the shield in which hits are parried.
+    pub const PARRY_WINDOW: f64 = 0.05;
+    /// Seconds the player is stunned for when the shield breaks.
+    pub const BREAK_STUN: f64 = 2.0;
+
+    pub fn new(max_health: f64) -> Self {
+        Self {
+            health: max_health,
+            max_health,
+            raised: None,
+        }
+    }
+
+    /// Defence points make the shield last longer.
+    pub fn from_defence(defence_points: i64) -> Self {
+        let base_health = 50.0;
+        let health_per_defence = 2.5;
+        Self::new(base_health + defence_points as f64 * health_per_defence)
+    }
+
+    /// Raises the shield while held, a broken shield can't be raised until
+    /// it has regenerated a bit. Returns true if holding it up drained it
+    /// completely, which breaks it like a hit would.
+    pub fn update(&mut self, delta: f64, held: bool) -> bool {
+        let drain = 4.0;
+        let regeneration = 12.0;
+        let min_raise_health = self.max_health * 0.2;
+        self.raised = match self.raised {
+            Some(time) if held => Some(time + delta),
+            None if held && self.health > 0.0 && self.health >= min_raise_health => Some(0.0),
+            _ => None,
+        };
+        if self.raised.is_none() {
+            self.health = (self.health + regeneration * delta).min(self.max_health);
+            return false;
+        }
+        self.health -= drain * delta;
+        if self.health > 0.0 {
+            return false;
+        }
+        self.health = 0.0;
+        self.raised = None;
+        true
+    }
+
+    pub fn raised(&self) -> bool {
+        self.raised.is_some()
+    }
+
+    pub fn parrying(&self) -> bool {
+        self.raised.is_some_and(|time| time < Self::PARRY_WINDOW)
+    }
+
+    /// Takes the damage of a hit, returns true if the shield broke.
+    pub fn absorb(&mut self, damage: f64) -> bool {
+        self.health -= damage;
+        if self.health > 0.0 {
+            return false;
+        }
+        self.health = 0.0;
+        self.raised = None;
+        true
+    }
+
+    pub fn lower(&mut self) {
+        self.raised = None;
+    }
+
+    /// Zero for players without a shield.
+    pub fn fraction(&self) -> f64 {
+        if self.max_health <= 0.0 {
+            return 0.0;
+        }
+        self.health / self.max_health
+    }
+}
+
+#[test]
+fn test_shield_parry_and_break() {
+    let mut shield = Shield::new(20.0);
+    shield.update(1.0 / 60.0, true);
+    assert!(shield.parrying());
+    shield.update(0.1, true);
+    assert!(shield.raised() && !shield.parrying());
+
+    assert!(!shield.absorb(10.0));
+    assert!(shield.absorb(10.0));
+    assert!(!shield.raised());
+
+    // too weak to raise until it has regenerated
+    shield.update(0.1, true);
+    assert!(!shield.raised());
+    for _ in 0..60 {
+        shield.update(1.0 / 60.0, false);
+    }
+    shield.update(1.0 / 60.0, true);
+    assert!(shield.raised());
+}
+
+#[test]
+fn test_shield_drain_and_none() {
+    let mut shield = Shield::new(10.0);
+    assert!(!shield.update(1.0, true));
+    assert!(shield.update(2.0, true));
+    assert!(!shield.raised());
+    assert_eq!(shield.health, 0.0);
+
+    let mut none = Shield::new(0.0);
+    assert!(!none.update(1.0 / 60.0, true));
+    assert!(!none.raised());
+    assert_eq!(none.fraction(), 0.0);
+}
@@ -8,6 +8,7 @@ pub struct Sprite {
     pub layer: i32,
     pub opacity: Option<f64>,
     pub angle: Option<f64>,
+    pub flip_horizontal: bool,
 }
 
 impl Sprite {
@@ -19,6 +20,7 @@ impl Sprite {
             size: None,
             opacity: None,
             angle: None,
+            flip_horizontal: false,
         }
     }
 
@@ -44,6 +46,13 @@ impl Sprite {
         }
     }
 
+    pub fn flip_horizontal(self, flip_horizontal: bool) -> Self {
+        Self {
+            flip_horizontal,
+            ..self
+        }
+    }
+
     pub fn set_opacity(&mut self, opacity: f64) {
         self.opacity = Some(opacity);
     }
@@ -69,7 +78,8 @@ impl System for SpriteRenderer {
                 DrawTextureOpts::new()
                     .size(size)
                     .opacity(opacity)
-                    .angle(sprite.angle),
+                    .angle(sprite.angle)
+                    .flip_horizontal(sprite.flip_horizontal),
             )?;
         }
         Ok(())
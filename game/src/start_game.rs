@@ -14,6 +14,9 @@ use crate::{
     game::GameSystem,
     hero_info::HeroInfo,
     main_menu::MainMenuSystem,
+    match_rules::MatchRules,
+    moves::MoveSet,
+    player::PlayerKind,
     server::{Board, HeroResult, Res, Server},
     sound_player::SoundPlayer,
     ui_components::{Button, ProgressBar},
@@ -27,6 +30,10 @@ pub struct StartGame {
     right_bars: SharedPtr<BarBundle>,
     board_responder: Option<SharedPtr<Box<dyn Res<Board>>>>,
     focus: SharedPtr<Focus>,
+    rules: MatchRules,
+    /// Set when the moves couldn't be loaded, the error stays up until it
+    /// is dismissed.
+    load_failed: bool,
 }
 
 #[repr(u64)]
@@ -41,12 +48,22 @@ enum Node {
     LeftOffset,
     RightOffset,
     StartGameButton,
+    StocksButton,
+    TimeLimitButton,
+    ModeButton,
+    LeftHandicapButton,
+    RightHandicapButton,
 }
 
 #[repr(u64)]
 enum Event {
     StartGame,
     ErrorPopupClick,
+    Stocks,
+    TimeLimit,
+    Mode,
+    LeftHandicap,
+    RightHandicap,
 }
 
 impl From<Node> for ui::NodeId {
@@ -85,6 +102,16 @@ impl System for StartGameSystem {
         let right_agility_bar = ProgressBar::new_immutable("Agility", 24);
         let right_defence_bar = ProgressBar::new_immutable("Defence", 24);
 
+        let rules = MatchRules::default();
+        let rule_button = |label: String, node: Node, event: Event| {
+            Button(label)
+                .id(node)
+                .color((255, 255, 255))
+                .padding(10)
+                .width(200)
+                .on_click(event)
+        };
+
         let mut dom = ui::Dom::new(
             Stack([
                 Hori([
@@ -105,10 +132,26 @@ impl System for StartGameSystem {
                             .width(200)
                             .height(200)
                             .background_color((255, 0, 0)),
+                        Rect().height(20),
+                        rule_button(
+                            rules.handicap_label(&PlayerKind::Left),
+                            Node::LeftHandicapButton,
+                            Event::LeftHandicap,
+                        ),
                     ]),
                     Rect().width(200),
                     Vert([
-                        Rect().height(400),
+                        Rect().height(200),
+                        rule_button(rules.stocks_label(), Node::StocksButton, Event::Stocks),
+                        Rect().height(10),
+                        rule_button(
+                            rules.time_limit_label(),
+                            Node::TimeLimitButton,
+                            Event::TimeLimit,
+                        ),
+                        Rect().height(10),
+                        rule_button(rules.mode_label(), Node::ModeButton, Event::Mode),
+                        Rect().height(40),
                         Button("Start Game")
                             .id(Node::StartGameButton)
                             .color((255, 255, 255))
@@ -133,6 +176,12 @@ impl System for StartGameSystem {
                             .width(200)
                             .height(200)
                             .background_color((255, 0, 0)),
+                        Rect().height(20),
+                        rule_button(
+                            rules.handicap_label(&PlayerKind::Right),
+                            Node::RightHandicapButton,
+                            Event::RightHandicap,
+                        ),
                     ]),
                 ]),
                 Vert([
@@ -154,14 +203,70 @@ impl System for StartGameSystem {
             .height(720),
         );
 
-        dom.add_event_handler(Event::StartGame, move |_dom, ctx, _node_id| {
+        dom.add_event_handler(Event::StartGame, move |dom, ctx, _node_id| {
             if let Some(heroes_on_board) = ctx.clone_one::<MaybeHeroesOnBoard>().0 {
+                let moves = match MoveSet::load() {
+                    Ok(moves) => moves,
+                    Err(err) => {
+                        println!("unable to load moves: {err}");
+                        ctx.select_one::<StartGame>().load_failed = true;
+                        let error = format!("Unable to load moves: {err}");
+                        change_text_node_content(dom.select_mut(Node::ErrorText), error);
+                        dom.select_mut(Node::ErrorPopup).unwrap().set_visible(true);
+                        return;
+                    }
+                };
+                let rules = ctx.select_one::<StartGame>().rules.clone();
+                spawn!(ctx, moves);
                 spawn!(ctx, heroes_on_board);
+                spawn!(ctx, rules);
                 ctx.remove_system(system_id);
                 ctx.add_system(GameSystem);
             }
         });
 
+        dom.add_event_handler(Event::Stocks, |dom, ctx, _node_id| {
+            let rules = &mut ctx.select_one::<StartGame>().rules;
+            rules.next_stocks();
+            let label = rules.stocks_label();
+            change_text_node_content(dom.select_mut(Node::StocksButton), label);
+        });
+
+        dom.add_event_handler(Event::TimeLimit, |dom, ctx, _node_id| {
+            let rules = &mut ctx.select_one::<StartGame>().rules;
+            rules.next_time_limit();
+            let label = rules.time_limit_label();
+            change_text_node_content(dom.select_mut(Node::TimeLimitButton), label);
+        });
+
+        dom.add_event_handler(Event::Mode, |dom, ctx, _node_id| {
+            let rules = &mut ctx.select_one::<StartGame>().rules;
+            rules.next_mode();
+            let label = rules.mode_label();
+            change_text_node_content(dom.select_mut(Node::ModeButton), label);
+        });
+
+        for (kind, node, event) in [
+            (
+                PlayerKind::Left,
+                Node::LeftHandicapButton,
+                Event::LeftHandicap,
+            ),
+            (
+                PlayerKind::Right,
+                Node::RightHandicapButton,
+                Event::RightHandicap,
+            ),
+        ] {
+            let node_id = ui::NodeId::from(node);
+            dom.add_event_handler(event, move |dom, ctx, _node_id| {
+                let rules = &mut ctx.select_one::<StartGame>().rules;
+                rules.next_handicap(&kind);
+                let label = rules.handicap_label(&kind);
+                change_text_node_content(dom.select_mut(node_id), label);
+            });
+        }
+
         dom.add_event_handler(Event::ErrorPopupClick, move |dom, ctx, _node_id| {
             ctx.remove_system(system_id);
             ctx.add_system(MainMenuSystem);
@@ -187,7 +292,17 @@ impl System for StartGameSystem {
                     defence: right_defence_bar
                 }),
                 board_responder: None,
-                focus: SharedPtr::new(Focus::new([Node::StartGameButton, Node::ErrorPopupButton]))
+                focus: SharedPtr::new(Focus::new([
+                    Node::LeftHandicapButton,
+                    Node::StocksButton,
+                    Node::TimeLimitButton,
+                    Node::ModeButton,
+                    Node::StartGameButton,
+                    Node::RightHandicapButton,
+                    Node::ErrorPopupButton,
+                ])),
+                rules,
+                load_failed: false,
             }
         );
 
@@ -201,6 +316,9 @@ impl System for StartGameSystem {
         let mut dom = start_game.dom.lock();
         dom.update(ctx);
         start_game.focus.lock().update(dom.borrow_mut(), ctx);
+        if start_game.load_failed {
+            return Ok(());
+        }
 
         let responder = match start_game.board_responder {
             Some(responder) => responder,
@@ -24,7 +24,7 @@ pub struct HeroStats {
 }
 
 #[repr(i64)]
-#[derive(Clone, Debug, ReprDeserialize, ReprSerialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, ReprDeserialize, ReprSerialize)]
 pub enum HeroKind {
     Centrist = 0,
     Strong = 1,
@@ -32,6 +32,15 @@ pub enum HeroKind {
     Tankie = 3,
 }
 
+impl HeroKind {
+    pub const ALL: [HeroKind; 4] = [
+        HeroKind::Centrist,
+        HeroKind::Strong,
+        HeroKind::Speed,
+        HeroKind::Tankie,
+    ];
+}
+
 impl std::fmt::Display for HeroKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let text = match self {